@@ -0,0 +1,88 @@
+// Subject: Goldilocks vs BLS12-381 scalar field
+//
+// Field multiplication, NTT and the whole FRI commit and decommit phases
+// run on the same polynomial over both fields.
+//
+//     cargo bench --bench fields
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use fri_basic_rustling::channel::Channel;
+use fri_basic_rustling::domain::two_adic_root_of_unity;
+use fri_basic_rustling::field::{FriField, TwoAdicField};
+use fri_basic_rustling::field_provider_goldilocks::Goldilocks;
+use fri_basic_rustling::field_provider_v1::FieldElement;
+use fri_basic_rustling::fri_code_layer::FriCodeLayer;
+use fri_basic_rustling::fri_config::FriConfig;
+use fri_basic_rustling::ntt::ntt;
+use fri_basic_rustling::polynome::Polynome;
+
+const FIELDS: [&str; 2] = ["goldilocks", "bls12_381"];
+
+fn sample<F: FriField>(n: usize) -> Vec<F> {
+    (0..n as u64).map(|i| F::from(i * i + 7)).collect()
+}
+
+fn mul_chain<F: FriField>(values: &[F]) -> F {
+    values.iter().fold(F::ONE, |acc, x| acc * x)
+}
+
+fn fri_prove<F: TwoAdicField>(coefficients: &[F], domain_size: usize) {
+    let poly = Polynome::new_poly(coefficients);
+    let config = FriConfig::default();
+    let mut channel = Channel::<F>::new();
+    let (_, fri_layers) =
+        FriCodeLayer::<F>::fri_commit_phase(poly, domain_size, &config, &mut channel);
+    FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+}
+
+fn bench_multiplication(c: &mut Criterion) {
+    let mut group = c.benchmark_group("multiplication");
+    let goldilocks = sample::<Goldilocks>(1024);
+    let bls = sample::<FieldElement>(1024);
+
+    group.bench_function(FIELDS[0], |b| b.iter(|| mul_chain(black_box(&goldilocks))));
+    group.bench_function(FIELDS[1], |b| b.iter(|| mul_chain(black_box(&bls))));
+    group.finish();
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ntt");
+    for log_size in [10u32, 14] {
+        let size = 1usize << log_size;
+        let goldilocks = sample::<Goldilocks>(size);
+        let goldilocks_root = two_adic_root_of_unity::<Goldilocks>(log_size);
+        let bls = sample::<FieldElement>(size);
+        let bls_root = two_adic_root_of_unity::<FieldElement>(log_size);
+
+        group.bench_with_input(BenchmarkId::new(FIELDS[0], size), &size, |b, _| {
+            b.iter(|| ntt(&mut goldilocks.clone(), &goldilocks_root))
+        });
+        group.bench_with_input(BenchmarkId::new(FIELDS[1], size), &size, |b, _| {
+            b.iter(|| ntt(&mut bls.clone(), &bls_root))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fri(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fri_commit_and_decommit");
+    group.sample_size(10);
+    for degree in [255usize, 1023] {
+        // Same configuration as the main example
+        let domain_size = FriConfig::default().domain_size(degree);
+        let goldilocks = sample::<Goldilocks>(degree + 1);
+        let bls = sample::<FieldElement>(degree + 1);
+
+        group.bench_with_input(BenchmarkId::new(FIELDS[0], degree), &degree, |b, _| {
+            b.iter(|| fri_prove(&goldilocks, domain_size))
+        });
+        group.bench_with_input(BenchmarkId::new(FIELDS[1], degree), &degree, |b, _| {
+            b.iter(|| fri_prove(&bls, domain_size))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_multiplication, bench_ntt, bench_fri);
+criterion_main!(benches);
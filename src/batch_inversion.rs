@@ -0,0 +1,143 @@
+// Subject: Montgomery batch inversion
+//
+// The inverses of n elements cost a single field inversion and 3(n - 1) multiplications :
+// the prefix products a_0 a_1 ... a_i are computed, their last one is inverted, then
+// each inverse is read back from the end as (a_0 ... a_i)^-1 * (a_0 ... a_(i-1)).
+
+use ff::Field;
+
+// What to do with the zero elements, which have no inverse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroPolicy {
+    // Fail on the first zero element
+    Error,
+    // Leave zero elements as zero in the output, the others are inverted
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchInversionError {
+    // Index of the first zero element
+    ZeroElement(usize),
+}
+
+// Inverses of the values, in the same order
+pub fn batch_inverse<F: Field>(
+    values: &[F],
+    policy: ZeroPolicy,
+) -> Result<Vec<F>, BatchInversionError> {
+    let mut inverses = values.to_vec();
+    batch_inverse_in_place(&mut inverses, policy)?;
+    Ok(inverses)
+}
+
+// Replace each value by its inverse, the values are left untouched on error
+pub fn batch_inverse_in_place<F: Field>(
+    values: &mut [F],
+    policy: ZeroPolicy,
+) -> Result<(), BatchInversionError> {
+    if let Some(index) = values.iter().position(|v| bool::from(v.is_zero())) {
+        if policy == ZeroPolicy::Error {
+            return Err(BatchInversionError::ZeroElement(index));
+        }
+    }
+
+    // prefix[i] is the product of the non zero values before index i
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut product = F::ONE;
+    for v in values.iter() {
+        prefix.push(product);
+        if !bool::from(v.is_zero()) {
+            product *= v;
+        }
+    }
+
+    // The product of non zero values is never zero
+    let mut inverse = product.invert().unwrap();
+    for (v, p) in values.iter_mut().zip(prefix).rev() {
+        if bool::from(v.is_zero()) {
+            continue;
+        }
+        let value_inverse = inverse * p;
+        inverse *= *v;
+        *v = value_inverse;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_extension::QuadraticExtension;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+
+    #[test]
+    fn test_batch_inverse() {
+        let values = (1..=17)
+            .map(|i| FieldElement::from(i * 7 + 3))
+            .collect::<Vec<FieldElement>>();
+        let inverses = batch_inverse(&values, ZeroPolicy::Error).unwrap();
+
+        assert_eq!(inverses.len(), values.len());
+        values
+            .iter()
+            .zip(&inverses)
+            .for_each(|(v, i)| assert_eq!(*i, v.invert().unwrap()));
+
+        assert_eq!(
+            batch_inverse::<FieldElement>(&[], ZeroPolicy::Error),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_batch_inverse_in_extension() {
+        type E = QuadraticExtension<Goldilocks>;
+        let mut values = (1..=9)
+            .map(|i| E::x() * Goldilocks::from(i) + E::from(i * i))
+            .collect::<Vec<E>>();
+        let expected = values
+            .iter()
+            .map(|v| v.invert().unwrap())
+            .collect::<Vec<E>>();
+
+        batch_inverse_in_place(&mut values, ZeroPolicy::Error).unwrap();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_zero_policy() {
+        let values = vec![
+            FieldElement::from(2u64),
+            FieldElement::ZERO,
+            FieldElement::from(5u64),
+            FieldElement::ZERO,
+        ];
+
+        assert_eq!(
+            batch_inverse(&values, ZeroPolicy::Error),
+            Err(BatchInversionError::ZeroElement(1))
+        );
+
+        // The values are not modified on error
+        let mut in_place = values.clone();
+        assert!(batch_inverse_in_place(&mut in_place, ZeroPolicy::Error).is_err());
+        assert_eq!(in_place, values);
+
+        assert_eq!(
+            batch_inverse(&values, ZeroPolicy::Skip),
+            Ok(vec![
+                FieldElement::from(2u64).invert().unwrap(),
+                FieldElement::ZERO,
+                FieldElement::from(5u64).invert().unwrap(),
+                FieldElement::ZERO,
+            ])
+        );
+        assert_eq!(
+            batch_inverse(&[FieldElement::ZERO], ZeroPolicy::Skip),
+            Ok(vec![FieldElement::ZERO])
+        );
+    }
+}
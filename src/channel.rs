@@ -1,42 +1,161 @@
-use rand::Rng;
-use std::collections::HashMap;
+use crate::field::FriField;
+use crate::hasher::{MerkleHasher, Sha256};
+use crate::merkle_cap::decode_cap;
+use crate::transcript::Transcript;
 
-use crate::field_provider_v1::FieldElement;
+// Default domain-separation label of the FRI protocol
+pub const FRI_PROTOCOL_LABEL: &[u8] = b"fri_basic_rustling";
 
+// Non-interactive channel : challenges and query indices are derived by Fiat-Shamir
+// from the transcript of the committed Merkle roots, hashed with H
 #[derive(Clone, Debug)]
-pub struct Channel {
-    committed_merkle_root_by_challenge: HashMap<FieldElement, Option<String>>,
+pub struct Channel<F: FriField, H: MerkleHasher = Sha256> {
+    // Merkle roots in commitment order with the challenge they are mapped to,
+    // the first one being the key of the initial layer
+    committed_merkle_root_by_challenge: Vec<(F, Option<String>)>,
+    label: Vec<u8>,
+    transcript: Transcript<H>,
+    // Proof of work nonce absorbed before the queries, if any
+    pow_nonce: Option<u64>,
+    // DEEP-FRI evaluation f(z) at the out of domain point, if any
+    deep_evaluation: Option<F>,
 }
 
-impl Channel {
+impl<F: FriField, H: MerkleHasher> Default for Channel<F, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FriField, H: MerkleHasher> Channel<F, H> {
     pub fn new() -> Self {
+        Self::new_with_label(FRI_PROTOCOL_LABEL)
+    }
+
+    pub fn new_with_label(label: &[u8]) -> Self {
         Self {
-            committed_merkle_root_by_challenge: HashMap::new(),
+            committed_merkle_root_by_challenge: vec![],
+            label: label.to_vec(),
+            transcript: Transcript::new(label),
+            pow_nonce: None,
+            deep_evaluation: None,
+        }
+    }
+
+    pub fn get_label(&self) -> &[u8] {
+        &self.label
+    }
+
+    pub fn get_challenge(&mut self) -> F {
+        self.transcript.squeeze_field_element(b"beta")
+    }
+
+    pub fn get_index(&mut self) -> usize {
+        self.transcript.squeeze_index(b"query")
+    }
+
+    // Prover side of the grinding : find a proof of work nonce and absorb it,
+    // so that the queries depend on it
+    pub fn grind(&mut self, bits: u32) -> u64 {
+        let nonce = self.transcript.grind(b"pow_nonce", bits);
+        self.absorb_pow_nonce(nonce);
+        nonce
+    }
+
+    // Verifier side of the grinding : check the prover nonce and absorb it
+    pub fn check_proof_of_work(&mut self, bits: u32, nonce: u64) -> bool {
+        let valid = self
+            .transcript
+            .check_proof_of_work(b"pow_nonce", bits, nonce);
+        self.absorb_pow_nonce(nonce);
+        valid
+    }
+
+    fn absorb_pow_nonce(&mut self, nonce: u64) {
+        self.transcript.absorb(b"pow_nonce", &nonce.to_le_bytes());
+        self.pow_nonce = Some(nonce);
+    }
+
+    pub fn get_pow_nonce(&self) -> Option<u64> {
+        self.pow_nonce
+    }
+
+    // DEEP-FRI out of domain point z, sampled once the first layer is committed
+    pub fn get_deep_point(&mut self) -> F {
+        self.transcript.squeeze_field_element(b"deep_point")
+    }
+
+    // The prover sends f(z), the folding challenges depend on it
+    pub fn add_deep_evaluation(&mut self, value: F) {
+        self.transcript
+            .absorb_field_elements(b"deep_evaluation", &[value]);
+        self.deep_evaluation = Some(value);
+    }
+
+    pub fn get_deep_evaluation(&self) -> Option<F> {
+        self.deep_evaluation
+    }
+
+    // Degree correction of a quotient, sampled once its point and value are sent
+    pub fn get_degree_correction_challenge(&mut self) -> F {
+        self.transcript.squeeze_field_element(b"degree_correction")
+    }
+
+    // Random combination challenge of a batch, sampled once the batch is committed
+    pub fn get_batch_challenge(&mut self) -> F {
+        self.transcript.squeeze_field_element(b"batch_combination")
+    }
+
+    // Zero-knowledge : coefficient of the masking polynomial, sampled once it is committed
+    pub fn get_mask_challenge(&mut self) -> F {
+        self.transcript.squeeze_field_element(b"zk_mask")
+    }
+
+    // Opening of a commitment at a point to a claimed value, sent by the prover
+    pub fn add_opening(&mut self, point: F, value: F) {
+        self.transcript
+            .absorb_field_elements(b"opening_point", &[point]);
+        self.transcript
+            .absorb_field_elements(b"opening_value", &[value]);
+    }
+
+    // The cap digests are absorbed as digests, a root which is not a cap as its bytes
+    pub fn add_committed_data(&mut self, beta_challenge: F, merkel_root: Option<String>) {
+        let root = merkel_root.as_deref().unwrap_or_default();
+        match decode_cap(root) {
+            Some(cap) => self.transcript.absorb_digests(b"merkle_root", &cap),
+            None => self.transcript.absorb(b"merkle_root", root.as_bytes()),
         }
+        self.committed_merkle_root_by_challenge
+            .push((beta_challenge, merkel_root));
     }
 
-    pub fn get_challenge(&self) -> FieldElement {
-        return FieldElement::from(rand::thread_rng().gen::<u64>());
+    // The final polynomial is sent in the clear, the queries depend on it
+    pub fn add_final_polynomial(&mut self, coefficients: &[F]) {
+        self.transcript
+            .absorb_field_elements(b"final_polynomial", coefficients);
     }
 
-    pub fn get_index(&self) -> usize {
-        return rand::thread_rng().gen::<usize>();
+    pub fn get_merkle_root(&self, beta_challenge: F) -> Option<String> {
+        self.committed_merkle_root_by_challenge
+            .iter()
+            .find(|(challenge, _)| *challenge == beta_challenge)
+            .and_then(|(_, root)| root.clone())
     }
 
-    pub fn add_committed_data(
-        &mut self,
-        beta_challenge: FieldElement,
-        merkel_root: Option<String>,
-    ) {
+    pub fn get_committed_challenges(&self) -> Vec<F> {
         self.committed_merkle_root_by_challenge
-            .insert(beta_challenge, merkel_root);
+            .iter()
+            .map(|(challenge, _)| *challenge)
+            .collect()
     }
 
-    pub fn get_merkle_root(&self, beta_challenge: FieldElement) -> Option<String> {
+    // Committed roots in commitment order
+    pub fn get_committed_roots(&self) -> Vec<Option<String>> {
         self.committed_merkle_root_by_challenge
-            .get(&beta_challenge)
-            .cloned()
-            .flatten()
+            .iter()
+            .map(|(_, root)| root.clone())
+            .collect()
     }
 }
 
@@ -44,14 +163,114 @@ impl Channel {
 mod tests {
 
     use super::*;
+    use crate::field_provider_v1::FieldElement;
+    use crate::hasher::Poseidon2;
 
     #[test]
     fn test_channel() {
-        let mut channel = Channel::new();
+        let mut channel = Channel::<FieldElement>::new();
         let beta_challenge = channel.get_challenge();
         let merkle_root = Some("0x1234".to_string());
         channel.add_committed_data(beta_challenge, merkle_root.clone());
 
         assert_eq!(channel.get_merkle_root(beta_challenge), merkle_root);
     }
+
+    #[test]
+    fn test_committed_challenges_order() {
+        let mut channel = Channel::<FieldElement>::new();
+        let challenges = vec![
+            FieldElement::from(0u64),
+            FieldElement::from(42u64),
+            FieldElement::from(7u64),
+        ];
+        challenges
+            .iter()
+            .for_each(|c| channel.add_committed_data(*c, Some(format!("0x{:?}", c))));
+
+        assert_eq!(channel.get_committed_challenges(), challenges);
+        assert_eq!(
+            channel.get_committed_roots(),
+            challenges
+                .iter()
+                .map(|c| Some(format!("0x{:?}", c)))
+                .collect::<Vec<Option<String>>>()
+        );
+    }
+
+    #[test]
+    fn test_channel_replay() {
+        let mut prover_channel = Channel::<FieldElement>::new();
+        let mut verifier_channel = Channel::<FieldElement>::new();
+
+        prover_channel.add_committed_data(FieldElement::from(0u64), Some("0x1234".to_string()));
+        verifier_channel.add_committed_data(FieldElement::from(0u64), Some("0x1234".to_string()));
+
+        assert_eq!(
+            prover_channel.get_challenge(),
+            verifier_channel.get_challenge()
+        );
+        assert_eq!(prover_channel.get_index(), verifier_channel.get_index());
+    }
+
+    #[test]
+    fn test_channel_label() {
+        let mut channel = Channel::<FieldElement>::new_with_label(b"another protocol");
+        let mut default_channel = Channel::<FieldElement>::new();
+
+        assert_eq!(channel.get_label(), b"another protocol");
+        assert_ne!(channel.get_challenge(), default_channel.get_challenge());
+    }
+
+    #[test]
+    fn test_channel_grinding() {
+        let mut prover_channel = Channel::<FieldElement>::new();
+        let mut verifier_channel = prover_channel.clone();
+
+        let nonce = prover_channel.grind(6);
+        assert_eq!(prover_channel.get_pow_nonce(), Some(nonce));
+
+        // The prover nonce is the smallest valid one
+        assert!((0..nonce).all(|n| !verifier_channel.clone().check_proof_of_work(6, n)));
+
+        assert!(verifier_channel.check_proof_of_work(6, nonce));
+        assert_eq!(prover_channel.get_index(), verifier_channel.get_index());
+    }
+
+    #[test]
+    fn test_channel_deep_evaluation() {
+        let mut prover_channel = Channel::<FieldElement>::new();
+        let mut verifier_channel = prover_channel.clone();
+
+        let z = prover_channel.get_deep_point();
+        assert_eq!(verifier_channel.get_deep_point(), z);
+
+        prover_channel.add_deep_evaluation(FieldElement::from(5u64));
+        assert_eq!(
+            prover_channel.get_deep_evaluation(),
+            Some(FieldElement::from(5u64))
+        );
+
+        // The challenges depend on the evaluation sent
+        verifier_channel.add_deep_evaluation(FieldElement::from(6u64));
+        assert_ne!(
+            prover_channel.get_challenge(),
+            verifier_channel.get_challenge()
+        );
+    }
+
+    #[test]
+    fn test_channel_poseidon2_transcript() {
+        let mut prover_channel = Channel::<FieldElement, Poseidon2>::new();
+        let mut verifier_channel = Channel::<FieldElement, Poseidon2>::new();
+        let mut sha256_channel = Channel::<FieldElement>::new();
+
+        prover_channel.add_committed_data(FieldElement::from(0u64), Some("0x1234".to_string()));
+        verifier_channel.add_committed_data(FieldElement::from(0u64), Some("0x1234".to_string()));
+        sha256_channel.add_committed_data(FieldElement::from(0u64), Some("0x1234".to_string()));
+
+        let beta = prover_channel.get_challenge();
+        assert_eq!(verifier_channel.get_challenge(), beta);
+        assert_ne!(sha256_channel.get_challenge(), beta);
+    }
 }
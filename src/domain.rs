@@ -0,0 +1,203 @@
+// Subject: Evaluation domains over two-adic multiplicative subgroups and their cosets
+
+use std::ops::Index;
+
+use crate::batch_inversion::{batch_inverse, ZeroPolicy};
+use crate::field::TwoAdicField;
+
+// Domain { shift * g^i, 0 <= i < size } where g is a primitive size-th root of unity
+// The size is a power of two so g^(size/2) = -1 and domain[i + size/2] = -domain[i]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Domain<F: TwoAdicField> {
+    log_size: u32,
+    generator: F,
+    shift: F,
+    elements: Vec<F>,
+}
+
+// Primitive root of unity of order 2^log_size, taken from the field 2^S root of unity
+pub fn two_adic_root_of_unity<F: TwoAdicField>(log_size: u32) -> F {
+    assert!(
+        log_size <= F::two_adicity(),
+        "The field two-adicity is too small for this domain size"
+    );
+    let mut root = F::root_of_unity();
+    for _ in log_size..F::two_adicity() {
+        root = root.square();
+    }
+    root
+}
+
+impl<F: TwoAdicField> Domain<F> {
+    // Multiplicative subgroup of the given size
+    pub fn new(size: usize) -> Self {
+        Domain::new_coset(size, F::ONE)
+    }
+
+    // Coset of the multiplicative subgroup of the given size, shifted by `shift`
+    pub fn new_coset(size: usize, shift: F) -> Self {
+        assert!(
+            size.is_power_of_two(),
+            "Domain size should be a power of two"
+        );
+        assert!(shift != F::ZERO, "Coset shift should not be zero");
+
+        let log_size = size.trailing_zeros();
+        let generator = two_adic_root_of_unity(log_size);
+
+        let mut elements = Vec::with_capacity(size);
+        let mut current = shift;
+        for _ in 0..size {
+            elements.push(current);
+            current *= generator;
+        }
+
+        Self {
+            log_size,
+            generator,
+            shift,
+            elements,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    pub fn generator(&self) -> F {
+        self.generator
+    }
+
+    pub fn shift(&self) -> F {
+        self.shift
+    }
+
+    pub fn element(&self, index: usize) -> F {
+        self.elements[index]
+    }
+
+    pub fn elements(&self) -> &[F] {
+        &self.elements
+    }
+
+    // 1 / x for the first half of the domain, with a single field inversion
+    // The second half holds the opposites -x, whose inverses are -1 / x
+    pub fn inverse_first_half(&self) -> Vec<F> {
+        batch_inverse(&self.elements[..self.size() / 2], ZeroPolicy::Error)
+            .expect("Domain elements are not zero")
+    }
+
+    // Image of the domain by x -> x^2 : half the size, generator and shift squared
+    pub fn square(&self) -> Domain<F> {
+        assert!(self.log_size > 0, "A domain of size 1 cannot be squared");
+
+        let elements = self
+            .elements
+            .iter()
+            .take(self.size() / 2)
+            .map(|x| x.square())
+            .collect::<Vec<F>>();
+
+        Self {
+            log_size: self.log_size - 1,
+            generator: self.generator.square(),
+            shift: self.shift.square(),
+            elements,
+        }
+    }
+}
+
+impl<F: TwoAdicField> Index<usize> for Domain<F> {
+    type Output = F;
+
+    fn index(&self, index: usize) -> &F {
+        &self.elements[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_provider_v1::FieldElement;
+    use ff::{Field, PrimeField};
+
+    #[test]
+    fn test_two_adic_root_of_unity() {
+        let log_size = 5;
+        let root: FieldElement = two_adic_root_of_unity(log_size);
+
+        assert_eq!(root.pow([1u64 << log_size]), FieldElement::ONE);
+        assert_eq!(root.pow([1u64 << (log_size - 1)]), -FieldElement::ONE);
+        assert_eq!(two_adic_root_of_unity::<FieldElement>(0), FieldElement::ONE);
+    }
+
+    #[test]
+    fn test_subgroup() {
+        let domain = Domain::<FieldElement>::new(8);
+
+        assert_eq!(domain.size(), 8);
+        assert_eq!(domain.log_size(), 3);
+        assert_eq!(domain[0], FieldElement::ONE);
+        assert_eq!(domain[1], domain.generator());
+        domain
+            .elements()
+            .iter()
+            .for_each(|x| assert_eq!(x.pow([8u64]), FieldElement::ONE));
+    }
+
+    #[test]
+    fn test_coset() {
+        let shift = FieldElement::MULTIPLICATIVE_GENERATOR;
+        let domain = Domain::new_coset(16, shift);
+        let subgroup = Domain::<FieldElement>::new(16);
+
+        assert_eq!(domain.shift(), shift);
+        (0..16).for_each(|i| {
+            assert_eq!(domain.element(i), shift * subgroup.element(i));
+            assert_ne!(domain.element(i).pow([16u64]), FieldElement::ONE);
+        });
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let domain = Domain::new_coset(1024, FieldElement::MULTIPLICATIVE_GENERATOR);
+        let half = domain.size() / 2;
+
+        (0..half).for_each(|i| assert_eq!(domain[i + half], -domain[i]));
+    }
+
+    #[test]
+    fn test_square() {
+        let domain = Domain::new_coset(16, FieldElement::MULTIPLICATIVE_GENERATOR);
+        let squared = domain.square();
+
+        assert_eq!(
+            squared,
+            Domain::new_coset(8, FieldElement::MULTIPLICATIVE_GENERATOR.square())
+        );
+        (0..16).for_each(|i| assert_eq!(squared[i % 8], domain[i].square()));
+    }
+
+    #[test]
+    fn test_inverse_first_half() {
+        let domain = Domain::new_coset(32, FieldElement::MULTIPLICATIVE_GENERATOR);
+        let inverses = domain.inverse_first_half();
+
+        assert_eq!(inverses.len(), 16);
+        inverses.iter().enumerate().for_each(|(i, inv)| {
+            assert_eq!(*inv * domain[i], FieldElement::ONE);
+            assert_eq!(-*inv * domain[i + 16], FieldElement::ONE);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Domain size should be a power of two")]
+    fn test_non_power_of_two_size() {
+        Domain::<FieldElement>::new(48);
+    }
+}
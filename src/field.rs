@@ -0,0 +1,208 @@
+// Subject: Field abstraction of the FRI code
+//
+// Everything the FRI code needs from a field on top of the `ff::Field` arithmetic :
+// - `FriField` : a canonical byte encoding for hashing and serialization, and the
+//   sampling of challenges from random bytes
+// - `TwoAdicField` : the two-adic structure to build the domains
+// - `ExtensionField` : a field holding a base field, the codewords and challenges
+//   live in the extension while the domains stay in the base field
+// Every `ff::PrimeField` is a `TwoAdicField` and an extension of degree 1 of itself.
+
+use std::ops::{Mul, MulAssign};
+
+use ff::{Field, PrimeField};
+
+pub trait FriField: Field + From<u64> {
+    // log2 of the number of elements, rounded up
+    fn field_size_bits() -> u32;
+
+    // Number of bytes of the canonical encoding
+    fn encoded_len() -> usize;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    // None if the bytes are not a canonical encoding
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+
+    // Number of random bytes needed to sample an element
+    fn uniform_bytes_len() -> usize;
+
+    // Element derived from uniform_bytes_len() random bytes, close to uniform
+    fn from_uniform_bytes(bytes: &[u8]) -> Self;
+
+    // Modulus of the prime subfield, as `PrimeField::MODULUS`. The encoding of an element
+    // is the concatenation of the encodings of its coefficients in this subfield, so an
+    // algebraic hash over the same prime field absorbs them as native words
+    fn prime_subfield_modulus() -> &'static str;
+}
+
+pub trait TwoAdicField: FriField {
+    // S such that 2^S divides the order of the multiplicative group
+    fn two_adicity() -> u32;
+
+    // Primitive root of unity of order 2^S
+    fn root_of_unity() -> Self;
+
+    // Generator of the multiplicative group, used as coset shift
+    fn multiplicative_generator() -> Self;
+}
+
+// Field of degree DEGREE over F, seen as a vector space with basis 1, X, ..., X^(DEGREE-1)
+pub trait ExtensionField<F: FriField>: FriField + Mul<F, Output = Self> + MulAssign<F> {
+    const DEGREE: usize;
+
+    fn from_base(value: F) -> Self;
+
+    // Panics if the number of coefficients is not DEGREE
+    fn from_base_coefficients(coefficients: &[F]) -> Self;
+
+    fn to_base_coefficients(&self) -> Vec<F>;
+
+    // Some if the element belongs to the base field
+    fn as_base(&self) -> Option<F> {
+        let coefficients = self.to_base_coefficients();
+        if coefficients[1..].iter().all(|c| *c == F::ZERO) {
+            Some(coefficients[0])
+        } else {
+            None
+        }
+    }
+}
+
+impl<F: PrimeField> FriField for F {
+    fn field_size_bits() -> u32 {
+        F::NUM_BITS
+    }
+
+    fn encoded_len() -> usize {
+        F::Repr::default().as_ref().len()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_repr().as_ref().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut repr = F::Repr::default();
+        if bytes.len() != repr.as_ref().len() {
+            return None;
+        }
+        repr.as_mut().copy_from_slice(bytes);
+        Option::from(F::from_repr(repr))
+    }
+
+    // 512 bits reduced modulo p, the bias is negligible for fields up to 256 bits
+    fn uniform_bytes_len() -> usize {
+        64
+    }
+
+    fn from_uniform_bytes(bytes: &[u8]) -> Self {
+        let two_pow_32 = F::from(1u64 << 32);
+        let two_pow_64 = two_pow_32 * two_pow_32;
+        bytes.chunks(8).fold(F::ZERO, |acc, limb| {
+            acc * two_pow_64
+                + F::from(u64::from_be_bytes(
+                    limb.try_into().expect("Chunks of 8 bytes"),
+                ))
+        })
+    }
+
+    fn prime_subfield_modulus() -> &'static str {
+        F::MODULUS
+    }
+}
+
+impl<F: PrimeField> TwoAdicField for F {
+    fn two_adicity() -> u32 {
+        F::S
+    }
+
+    fn root_of_unity() -> Self {
+        F::ROOT_OF_UNITY
+    }
+
+    fn multiplicative_generator() -> Self {
+        F::MULTIPLICATIVE_GENERATOR
+    }
+}
+
+impl<F: FriField> ExtensionField<F> for F {
+    const DEGREE: usize = 1;
+
+    fn from_base(value: F) -> Self {
+        value
+    }
+
+    fn from_base_coefficients(coefficients: &[F]) -> Self {
+        assert_eq!(coefficients.len(), 1, "One coefficient per degree expected");
+        coefficients[0]
+    }
+
+    fn to_base_coefficients(&self) -> Vec<F> {
+        vec![*self]
+    }
+
+    fn as_base(&self) -> Option<F> {
+        Some(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_provider_babybear::BabyBear;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+
+    fn check_two_adic_structure<F: TwoAdicField>() {
+        let mut root = F::root_of_unity();
+        for _ in 0..F::two_adicity() - 1 {
+            root = root.square();
+        }
+        // root is now of order 2
+        assert_eq!(root, -F::ONE);
+    }
+
+    fn check_encoding<F: FriField>() {
+        let value = F::from(123456789u64);
+        let bytes = value.to_bytes();
+
+        assert_eq!(bytes.len(), F::encoded_len());
+        assert_eq!(F::from_bytes(&bytes), Some(value));
+        assert_eq!(F::from_bytes(&bytes[1..]), None);
+        assert_eq!(F::from_bytes(&vec![0xff; F::encoded_len()]), None);
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_field() {
+        check_two_adic_structure::<FieldElement>();
+        check_encoding::<FieldElement>();
+        assert_eq!(FieldElement::field_size_bits(), 255);
+    }
+
+    #[test]
+    fn test_babybear_field() {
+        check_two_adic_structure::<BabyBear>();
+        check_encoding::<BabyBear>();
+        assert_eq!(BabyBear::two_adicity(), 27);
+        assert_eq!(BabyBear::field_size_bits(), 31);
+    }
+
+    #[test]
+    fn test_goldilocks_field() {
+        check_two_adic_structure::<Goldilocks>();
+        check_encoding::<Goldilocks>();
+        assert_eq!(Goldilocks::two_adicity(), 32);
+        assert_eq!(Goldilocks::field_size_bits(), 64);
+    }
+
+    #[test]
+    fn test_base_field_is_its_own_extension() {
+        let value = Goldilocks::from(42u64);
+
+        assert_eq!(<Goldilocks as ExtensionField<Goldilocks>>::DEGREE, 1);
+        assert_eq!(value.to_base_coefficients(), vec![value]);
+        assert_eq!(value.as_base(), Some(value));
+    }
+}
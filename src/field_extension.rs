@@ -0,0 +1,519 @@
+// Subject: Binomial extensions of a prime field
+//
+// F[X] / (X^D - W) where W is the multiplicative generator g of F. The binomial is
+// irreducible when every prime factor of D divides p - 1 (g is then not a q-th power)
+// which holds for D = 2, 3, 4 over BabyBear, Goldilocks and BLS12-381.
+//
+// Over 31 or 64 bits fields a beta challenge taken in the base field leaves the prover
+// a non negligible chance to cheat, so the challenges are sampled in an extension of
+// degree 2, 3 or 4 while the first codeword and the domains stay in the base field.
+//
+// The arithmetic is not constant time.
+
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use crate::field::{ExtensionField, FriField};
+
+// Element c_0 + c_1 X + ... + c_(D-1) X^(D-1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BinomialExtension<F: PrimeField, const D: usize>(pub [F; D]);
+
+pub type QuadraticExtension<F> = BinomialExtension<F, 2>;
+pub type CubicExtension<F> = BinomialExtension<F, 3>;
+pub type QuarticExtension<F> = BinomialExtension<F, 4>;
+
+impl<F: PrimeField, const D: usize> BinomialExtension<F, D> {
+    // W such that X^D = W
+    pub fn non_residue() -> F {
+        F::MULTIPLICATIVE_GENERATOR
+    }
+
+    // Generator X of the extension
+    pub fn x() -> Self {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[1 % D] += F::ONE;
+        Self(coefficients)
+    }
+
+    // Number of elements p^D as little endian limbs
+    fn order() -> Vec<u64> {
+        let p = base_modulus::<F>();
+        (1..D).fold(p.clone(), |acc, _| mul_limbs(&acc, &p))
+    }
+
+    // First element X + k which is not a square
+    fn non_square() -> Self {
+        let half_order = shift_right(&sub_small(&Self::order(), 1), 1);
+        (0u64..)
+            .map(|k| Self::x() + Self::from(k))
+            .find(|z| z.pow_vartime(&half_order) == -Self::ONE)
+            .expect("Half of the elements are not squares")
+    }
+}
+
+impl<F: PrimeField, const D: usize> Default for BinomialExtension<F, D> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<F: PrimeField, const D: usize> From<u64> for BinomialExtension<F, D> {
+    fn from(value: u64) -> Self {
+        Self::from_base(F::from(value))
+    }
+}
+
+impl<F: PrimeField, const D: usize> ConstantTimeEq for BinomialExtension<F, D> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(Choice::from(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+impl<F: PrimeField, const D: usize> ConditionallySelectable for BinomialExtension<F, D> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(std::array::from_fn(|i| {
+            F::conditional_select(&a.0[i], &b.0[i], choice)
+        }))
+    }
+}
+
+impl<F: PrimeField, const D: usize> Neg for BinomialExtension<F, D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|c| -c))
+    }
+}
+
+impl<'a, F: PrimeField, const D: usize> Add<&'a BinomialExtension<F, D>>
+    for BinomialExtension<F, D>
+{
+    type Output = Self;
+
+    fn add(self, rhs: &'a BinomialExtension<F, D>) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<'a, F: PrimeField, const D: usize> Sub<&'a BinomialExtension<F, D>>
+    for BinomialExtension<F, D>
+{
+    type Output = Self;
+
+    fn sub(self, rhs: &'a BinomialExtension<F, D>) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+// Schoolbook product, X^(D + k) is reduced to W * X^k
+impl<'a, F: PrimeField, const D: usize> Mul<&'a BinomialExtension<F, D>>
+    for BinomialExtension<F, D>
+{
+    type Output = Self;
+
+    fn mul(self, rhs: &'a BinomialExtension<F, D>) -> Self {
+        let w = Self::non_residue();
+        let mut result = [F::ZERO; D];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                if i + j < D {
+                    result[i + j] += *a * b;
+                } else {
+                    result[i + j - D] += w * a * b;
+                }
+            }
+        }
+        Self(result)
+    }
+}
+
+// Product by a base field element, coefficient by coefficient
+impl<F: PrimeField, const D: usize> Mul<F> for BinomialExtension<F, D> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self {
+        Self(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<F: PrimeField, const D: usize> MulAssign<F> for BinomialExtension<F, D> {
+    fn mul_assign(&mut self, rhs: F) {
+        *self = *self * rhs;
+    }
+}
+
+// Owned and assigning variants of the operators, from the by reference ones
+macro_rules! impl_binary_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl<F: PrimeField, const D: usize> $trait for BinomialExtension<F, D> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                $trait::$method(self, &rhs)
+            }
+        }
+
+        impl<'a, F: PrimeField, const D: usize> $assign_trait<&'a BinomialExtension<F, D>>
+            for BinomialExtension<F, D>
+        {
+            fn $assign_method(&mut self, rhs: &'a BinomialExtension<F, D>) {
+                *self = $trait::$method(*self, rhs);
+            }
+        }
+
+        impl<F: PrimeField, const D: usize> $assign_trait for BinomialExtension<F, D> {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = $trait::$method(*self, &rhs);
+            }
+        }
+    };
+}
+
+impl_binary_operator!(Add, add, AddAssign, add_assign);
+impl_binary_operator!(Sub, sub, SubAssign, sub_assign);
+impl_binary_operator!(Mul, mul, MulAssign, mul_assign);
+
+impl<F: PrimeField, const D: usize> Sum for BinomialExtension<F, D> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a, F: PrimeField, const D: usize> Sum<&'a BinomialExtension<F, D>>
+    for BinomialExtension<F, D>
+{
+    fn sum<I: Iterator<Item = &'a BinomialExtension<F, D>>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: PrimeField, const D: usize> Product for BinomialExtension<F, D> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<'a, F: PrimeField, const D: usize> Product<&'a BinomialExtension<F, D>>
+    for BinomialExtension<F, D>
+{
+    fn product<I: Iterator<Item = &'a BinomialExtension<F, D>>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: PrimeField, const D: usize> Field for BinomialExtension<F, D> {
+    const ZERO: Self = Self([F::ZERO; D]);
+    const ONE: Self = {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = F::ONE;
+        Self(coefficients)
+    };
+
+    fn random(mut rng: impl RngCore) -> Self {
+        Self(std::array::from_fn(|_| F::random(&mut rng)))
+    }
+
+    fn square(&self) -> Self {
+        *self * self
+    }
+
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    // Fermat : x^(p^D - 2)
+    fn invert(&self) -> CtOption<Self> {
+        let exponent = sub_small(&Self::order(), 2);
+        CtOption::new(self.pow_vartime(&exponent), !self.is_zero())
+    }
+
+    // When num / div is not a square, the second value is the square root of
+    // non_square() * num / div, as in ff where the non square is ROOT_OF_UNITY
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        if bool::from(div.is_zero()) {
+            return (num.is_zero(), Self::ZERO);
+        }
+        let ratio = *num * div.invert().unwrap();
+        match Option::<Self>::from(ratio.sqrt()) {
+            Some(root) => (Choice::from(1), root),
+            None => (
+                Choice::from(0),
+                (ratio * Self::non_square())
+                    .sqrt()
+                    .expect("Product of two non squares is a square"),
+            ),
+        }
+    }
+
+    // Tonelli-Shanks with p^D - 1 = 2^s * t
+    fn sqrt(&self) -> CtOption<Self> {
+        if bool::from(self.is_zero()) {
+            return CtOption::new(Self::ZERO, Choice::from(1));
+        }
+        let order_minus_one = sub_small(&Self::order(), 1);
+        let s = trailing_zeros(&order_minus_one);
+        let t = shift_right(&order_minus_one, s);
+
+        let mut m = s;
+        let mut c = Self::non_square().pow_vartime(&t);
+        let mut x = self.pow_vartime(shift_right(&add_small(&t, 1), 1));
+        let mut b = self.pow_vartime(&t);
+
+        while b != Self::ONE {
+            // Least i such that b^(2^i) = 1
+            let mut i = 0;
+            let mut b_pow = b;
+            while b_pow != Self::ONE {
+                b_pow = b_pow.square();
+                i += 1;
+                if i == m {
+                    return CtOption::new(Self::ZERO, Choice::from(0));
+                }
+            }
+            let mut w = c;
+            for _ in 0..m - i - 1 {
+                w = w.square();
+            }
+            x *= w;
+            c = w.square();
+            b *= c;
+            m = i;
+        }
+        CtOption::new(x, Choice::from(1))
+    }
+}
+
+impl<F: PrimeField, const D: usize> FriField for BinomialExtension<F, D> {
+    fn field_size_bits() -> u32 {
+        D as u32 * F::NUM_BITS
+    }
+
+    fn encoded_len() -> usize {
+        D * F::encoded_len()
+    }
+
+    // Coefficients encodings one after the other, constant coefficient first
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|c| c.to_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::encoded_len() {
+            return None;
+        }
+        let coefficients = bytes
+            .chunks(F::encoded_len())
+            .map(F::from_bytes)
+            .collect::<Option<Vec<F>>>()?;
+        Some(Self::from_base_coefficients(&coefficients))
+    }
+
+    fn uniform_bytes_len() -> usize {
+        D * F::uniform_bytes_len()
+    }
+
+    fn from_uniform_bytes(bytes: &[u8]) -> Self {
+        Self(std::array::from_fn(|i| {
+            let len = F::uniform_bytes_len();
+            F::from_uniform_bytes(&bytes[i * len..(i + 1) * len])
+        }))
+    }
+
+    fn prime_subfield_modulus() -> &'static str {
+        F::MODULUS
+    }
+}
+
+impl<F: PrimeField, const D: usize> ExtensionField<F> for BinomialExtension<F, D> {
+    const DEGREE: usize = D;
+
+    fn from_base(value: F) -> Self {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = value;
+        Self(coefficients)
+    }
+
+    fn from_base_coefficients(coefficients: &[F]) -> Self {
+        Self(
+            coefficients
+                .try_into()
+                .expect("One coefficient per degree expected"),
+        )
+    }
+
+    fn to_base_coefficients(&self) -> Vec<F> {
+        self.0.to_vec()
+    }
+}
+
+// Big integers as little endian u64 limbs, only used to compute the exponents
+// of the inversion and square root in the extension
+
+// ff gives the modulus as a hexadecimal string
+fn base_modulus<F: PrimeField>() -> Vec<u64> {
+    let hex = F::MODULUS.trim_start_matches("0x");
+    hex.as_bytes()
+        .rchunks(16)
+        .map(|chunk| {
+            u64::from_str_radix(std::str::from_utf8(chunk).expect("Hexadecimal digits"), 16)
+                .expect("Hexadecimal modulus")
+        })
+        .collect()
+}
+
+fn mul_limbs(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut result = vec![0u64; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, y) in b.iter().enumerate() {
+            let t = *x as u128 * *y as u128 + result[i + j] as u128 + carry;
+            result[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        result[i + b.len()] = carry as u64;
+    }
+    result
+}
+
+fn add_small(a: &[u64], value: u64) -> Vec<u64> {
+    let mut result = a.to_vec();
+    result.push(0);
+    let mut carry = value;
+    for limb in result.iter_mut() {
+        let (sum, overflow) = limb.overflowing_add(carry);
+        *limb = sum;
+        carry = overflow as u64;
+    }
+    result
+}
+
+// a should be at least value
+fn sub_small(a: &[u64], value: u64) -> Vec<u64> {
+    let mut result = a.to_vec();
+    let mut borrow = value;
+    for limb in result.iter_mut() {
+        let (diff, underflow) = limb.overflowing_sub(borrow);
+        *limb = diff;
+        borrow = underflow as u64;
+    }
+    result
+}
+
+fn shift_right(a: &[u64], bits: u32) -> Vec<u64> {
+    let limbs = (bits / 64) as usize;
+    let bits = bits % 64;
+    (limbs..a.len())
+        .map(|i| {
+            let high = match (bits, a.get(i + 1)) {
+                (0, _) | (_, None) => 0,
+                (_, Some(next)) => next << (64 - bits),
+            };
+            (a[i] >> bits) | high
+        })
+        .collect()
+}
+
+fn trailing_zeros(a: &[u64]) -> u32 {
+    let mut zeros = 0;
+    for limb in a {
+        if *limb != 0 {
+            return zeros + limb.trailing_zeros();
+        }
+        zeros += 64;
+    }
+    zeros
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_provider_babybear::BabyBear;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn check_field_axioms<E: ExtensionField<F>, F: FriField>() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10 {
+            let a = E::random(&mut rng);
+            let b = E::random(&mut rng);
+            let c = E::random(&mut rng);
+
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * b, b * a);
+            assert_eq!(a * a.invert().unwrap(), E::ONE);
+            assert_eq!(a.square().sqrt().unwrap().square(), a.square());
+
+            let scalar = F::random(&mut rng);
+            assert_eq!(a * scalar, a * E::from_base(scalar));
+            assert_eq!(E::from_bytes(&a.to_bytes()), Some(a));
+        }
+        assert!(bool::from(E::ZERO.invert().is_none()));
+    }
+
+    #[test]
+    fn test_goldilocks_extensions() {
+        check_field_axioms::<QuadraticExtension<Goldilocks>, Goldilocks>();
+        check_field_axioms::<CubicExtension<Goldilocks>, Goldilocks>();
+        assert_eq!(QuadraticExtension::<Goldilocks>::field_size_bits(), 128);
+    }
+
+    #[test]
+    fn test_babybear_extensions() {
+        check_field_axioms::<QuadraticExtension<BabyBear>, BabyBear>();
+        check_field_axioms::<CubicExtension<BabyBear>, BabyBear>();
+        check_field_axioms::<QuarticExtension<BabyBear>, BabyBear>();
+        assert_eq!(QuarticExtension::<BabyBear>::field_size_bits(), 124);
+    }
+
+    #[test]
+    fn test_reduction_by_non_residue() {
+        type E = CubicExtension<Goldilocks>;
+        let x = E::x();
+        let w = E::non_residue();
+
+        assert_eq!(x * x * x, E::from_base(w));
+        assert_eq!(
+            (x * x).0,
+            [Goldilocks::ZERO, Goldilocks::ZERO, Goldilocks::ONE]
+        );
+        assert_eq!(ExtensionField::<Goldilocks>::as_base(&x), None);
+        assert_eq!(
+            ExtensionField::<Goldilocks>::as_base(&E::from_base(w)),
+            Some(w)
+        );
+    }
+
+    #[test]
+    fn test_non_square() {
+        let z = QuadraticExtension::<BabyBear>::non_square();
+        assert!(bool::from(z.sqrt().is_none()));
+
+        let (is_square, root) = QuadraticExtension::sqrt_ratio(&z, &QuadraticExtension::ONE);
+        assert!(!bool::from(is_square));
+        assert_eq!(root.square(), z * z);
+    }
+
+    #[test]
+    fn test_limbs() {
+        let p = base_modulus::<Goldilocks>();
+        assert_eq!(p, vec![0xffff_ffff_0000_0001]);
+
+        let p_square = mul_limbs(&p, &p);
+        let expected = 0xffff_ffff_0000_0001u128 * 0xffff_ffff_0000_0001u128;
+        assert_eq!(p_square, vec![expected as u64, (expected >> 64) as u64]);
+        assert_eq!(trailing_zeros(&sub_small(&p_square, 1)), 33);
+        assert_eq!(shift_right(&p_square, 64), vec![(expected >> 64) as u64]);
+        assert_eq!(add_small(&[u64::MAX], 1), vec![0, 1]);
+    }
+}
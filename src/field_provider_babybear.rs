@@ -0,0 +1,7 @@
+use ff::PrimeField;
+//BabyBear : p = 15 * 2^27 + 1
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "2013265921"]
+#[PrimeFieldGenerator = "31"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct BabyBear([u64; 1]);
@@ -0,0 +1,323 @@
+// Subject: Goldilocks field, p = 2^64 - 2^32 + 1
+//
+// Elements are stored as canonical u64 (no Montgomery form). The product of two
+// elements is reduced from 128 bits with 2^64 = 2^32 - 1 and 2^96 = -1 mod p,
+// which only needs a few 64 bits additions and subtractions.
+// The arithmetic is not constant time : it is meant for performance experiments.
+
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use ff::helpers::{sqrt_ratio_generic, sqrt_tonelli_shanks};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+// 2^64 - p = 2^32 - 1
+const EPSILON: u64 = 0xffff_ffff;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Goldilocks(u64);
+
+impl Goldilocks {
+    pub const fn new(value: u64) -> Self {
+        Self(if value >= MODULUS {
+            value - MODULUS
+        } else {
+            value
+        })
+    }
+
+    // Canonical representative in [0, p)
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+// x mod p for any 128 bits x = hi_hi * 2^96 + hi_lo * 2^64 + lo
+fn reduce128(x: u128) -> u64 {
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & EPSILON;
+
+    // lo - hi_hi, a borrow wraps by 2^64 so it is corrected by subtracting 2^64 - p
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+    // hi_lo * (2^32 - 1) fits in 64 bits
+    let t1 = hi_lo * EPSILON;
+
+    let (t2, carry) = t0.overflowing_add(t1);
+    let result = t2.wrapping_add(if carry { EPSILON } else { 0 });
+    Goldilocks::new(result).0
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Goldilocks::new(value)
+    }
+}
+
+impl ConstantTimeEq for Goldilocks {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for Goldilocks {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(u64::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.0 == 0 {
+            self
+        } else {
+            Self(MODULUS - self.0)
+        }
+    }
+}
+
+impl<'a> Add<&'a Goldilocks> for Goldilocks {
+    type Output = Self;
+
+    fn add(self, rhs: &'a Goldilocks) -> Self {
+        let (sum, carry) = self.0.overflowing_add(rhs.0);
+        let (sum, borrow) = sum.overflowing_sub(MODULUS);
+        // Both operands are below p : add back p if the subtraction was not needed
+        if carry || !borrow {
+            Self(sum)
+        } else {
+            Self(sum.wrapping_add(MODULUS))
+        }
+    }
+}
+
+impl<'a> Sub<&'a Goldilocks> for Goldilocks {
+    type Output = Self;
+
+    fn sub(self, rhs: &'a Goldilocks) -> Self {
+        let (diff, borrow) = self.0.overflowing_sub(rhs.0);
+        if borrow {
+            Self(diff.wrapping_add(MODULUS))
+        } else {
+            Self(diff)
+        }
+    }
+}
+
+impl<'a> Mul<&'a Goldilocks> for Goldilocks {
+    type Output = Self;
+
+    fn mul(self, rhs: &'a Goldilocks) -> Self {
+        Self(reduce128(self.0 as u128 * rhs.0 as u128))
+    }
+}
+
+// Owned and assigning variants of the operators, from the by reference ones
+macro_rules! impl_binary_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl $trait for Goldilocks {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                $trait::$method(self, &rhs)
+            }
+        }
+
+        impl<'a> $assign_trait<&'a Goldilocks> for Goldilocks {
+            fn $assign_method(&mut self, rhs: &'a Goldilocks) {
+                *self = $trait::$method(*self, rhs);
+            }
+        }
+
+        impl $assign_trait for Goldilocks {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = $trait::$method(*self, &rhs);
+            }
+        }
+    };
+}
+
+impl_binary_operator!(Add, add, AddAssign, add_assign);
+impl_binary_operator!(Sub, sub, SubAssign, sub_assign);
+impl_binary_operator!(Mul, mul, MulAssign, mul_assign);
+
+impl Sum for Goldilocks {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a> Sum<&'a Goldilocks> for Goldilocks {
+    fn sum<I: Iterator<Item = &'a Goldilocks>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl Product for Goldilocks {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<'a> Product<&'a Goldilocks> for Goldilocks {
+    fn product<I: Iterator<Item = &'a Goldilocks>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Field for Goldilocks {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+
+    // Rejection sampling of a canonical value
+    fn random(mut rng: impl RngCore) -> Self {
+        loop {
+            let value = rng.next_u64();
+            if value < MODULUS {
+                return Self(value);
+            }
+        }
+    }
+
+    fn square(&self) -> Self {
+        *self * self
+    }
+
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    // Fermat : x^(p - 2)
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([MODULUS - 2]), !self.is_zero())
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        sqrt_ratio_generic(num, div)
+    }
+
+    // p - 1 = 2^32 * t with t = 2^32 - 1
+    fn sqrt(&self) -> CtOption<Self> {
+        sqrt_tonelli_shanks(self, [(EPSILON - 1) / 2])
+    }
+}
+
+impl PrimeField for Goldilocks {
+    type Repr = [u8; 8];
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u64::from_le_bytes(repr);
+        CtOption::new(Self(value), Choice::from((value < MODULUS) as u8))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.0 & 1) as u8)
+    }
+
+    const MODULUS: &'static str = "0xffffffff00000001";
+    const NUM_BITS: u32 = 64;
+    const CAPACITY: u32 = 63;
+    const TWO_INV: Self = Self(0x7fff_ffff_8000_0001);
+    const MULTIPLICATIVE_GENERATOR: Self = Self(7);
+    const S: u32 = 32;
+    // 7^((p - 1) / 2^32)
+    const ROOT_OF_UNITY: Self = Self(1753635133440165772);
+    const ROOT_OF_UNITY_INV: Self = Self(8554224884056360729);
+    // 7^(2^32)
+    const DELTA: Self = Self(12275445934081160404);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Reference arithmetic over u128
+    fn mul_mod(a: u64, b: u64) -> u64 {
+        ((a as u128 * b as u128) % MODULUS as u128) as u64
+    }
+
+    #[test]
+    fn test_reduce128() {
+        let values = [
+            0u128,
+            1,
+            MODULUS as u128,
+            u64::MAX as u128,
+            (MODULUS as u128) << 64,
+            1u128 << 96,
+            (1u128 << 96) - 1,
+            u128::MAX,
+            (MODULUS as u128 - 1) * (MODULUS as u128 - 1),
+        ];
+        values
+            .iter()
+            .for_each(|x| assert_eq!(reduce128(*x), (x % MODULUS as u128) as u64));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let edges = [0, 1, 2, EPSILON, EPSILON + 1, MODULUS - 2, MODULUS - 1];
+        for a in edges {
+            for b in edges {
+                let (x, y) = (Goldilocks(a), Goldilocks(b));
+                assert_eq!(
+                    (x + y).0,
+                    ((a as u128 + b as u128) % MODULUS as u128) as u64
+                );
+                assert_eq!(
+                    (x - y).0,
+                    ((a as u128 + MODULUS as u128 - b as u128) % MODULUS as u128) as u64
+                );
+                assert_eq!((x * y).0, mul_mod(a, b));
+            }
+            assert_eq!(Goldilocks(a) + (-Goldilocks(a)), Goldilocks::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_invert_and_sqrt() {
+        let x = Goldilocks::from(123456789u64);
+        assert_eq!(x * x.invert().unwrap(), Goldilocks::ONE);
+        assert!(bool::from(Goldilocks::ZERO.invert().is_none()));
+
+        let root = x.square().sqrt().unwrap();
+        assert!(root == x || root == -x);
+        // The generator is not a square
+        assert!(bool::from(
+            Goldilocks::MULTIPLICATIVE_GENERATOR.sqrt().is_none()
+        ));
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(Goldilocks::TWO_INV.double(), Goldilocks::ONE);
+        assert_eq!(
+            Goldilocks::ROOT_OF_UNITY * Goldilocks::ROOT_OF_UNITY_INV,
+            Goldilocks::ONE
+        );
+        assert_eq!(
+            Goldilocks::MULTIPLICATIVE_GENERATOR.pow([EPSILON]),
+            Goldilocks::ROOT_OF_UNITY
+        );
+        assert_eq!(
+            Goldilocks::MULTIPLICATIVE_GENERATOR.pow([1u64 << 32]),
+            Goldilocks::DELTA
+        );
+        assert_eq!(Goldilocks::from(MODULUS), Goldilocks::ZERO);
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::derived_hash_with_manual_eq)]
+
 use ff::PrimeField;
 //BLS12-381
 #[derive(PrimeField, Hash)]
@@ -1,250 +1,1033 @@
 // Subject: FRI code layer implementation
+//
+// The domains live in the base field F. The first codeword is the evaluation of the
+// input polynomial, it lives in F and is committed as such. The beta challenges and
+// the folded codewords live in the extension E (E = F when no extension is used).
+
+use std::marker::PhantomData;
 
-use ff::{Field, PrimeField};
-use rs_merkle::algorithms::Sha256;
-use rs_merkle::Hasher;
 use rs_merkle::MerkleTree;
 
+use crate::batch_inversion::{
+    batch_inverse, batch_inverse_in_place, BatchInversionError, ZeroPolicy,
+};
 use crate::channel::Channel;
-use crate::field_provider_v1::FieldElement;
+use crate::domain::Domain;
+use crate::field::{ExtensionField, FriField, TwoAdicField};
+use crate::fri_config::FriConfig;
+use crate::hasher::{MerkleHasher, Sha256};
+use crate::merkle_cap::MerkleCapTree;
 use crate::polynome::Polynome;
 
-// Domain_size 8 time polynome degree
-pub fn generate_enlarged_evaluation_domain(domain_size: usize) -> Vec<FieldElement> {
-    let g = FieldElement::MULTIPLICATIVE_GENERATOR;
-    let coset_offset = g.pow(&[(2u64.pow(30) * 3) % domain_size as u64]); // coset_offset outside the generator powers
+// The evaluation domain is the coset of the two-adic subgroup of size domain_size
+// shifted by the multiplicative generator, so it never meets the subgroup itself
+pub fn generate_enlarged_evaluation_domain<F: TwoAdicField>(domain_size: usize) -> Domain<F> {
+    Domain::new_coset(domain_size, F::multiplicative_generator())
+}
+
+// Evaluate the polynomial on the enlarged domain
+pub fn evaluate_on_enlarged_domain<F: TwoAdicField, E: ExtensionField<F>>(
+    poly: &Polynome<E>,
+    dom: &Domain<F>,
+) -> Vec<E> {
+    // The domain is a two-adic coset so the NTT applies
+    poly.evaluate_over_domain(dom)
+}
 
-    let coset = (0..domain_size)
-        .map(|i| coset_offset.pow(&[i as u64]))
-        .collect::<Vec<FieldElement>>(); //generated by the coset_offset
+// Final polynomial and committed layers of the commitment phase
+pub type FriCommitment<F, E, H = Sha256> = (Polynome<E>, Vec<FriCodeLayer<F, E, H>>);
 
-    return coset.iter().map(|x| g * x).collect::<Vec<FieldElement>>(); //acting on the coset to have the eval_domain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotientError {
+    // The numerator does not fit the domain, the exactness cannot be checked
+    DomainTooSmall,
+    // Index of a domain element where the denominator is zero
+    DenominatorVanishes(usize),
+    // The denominator does not divide the numerator
+    NotDivisible,
 }
 
-// Evaluate the polynomial on the enlarged domain
-// In fact we need more sofisticated evaluation techniques to consider the  polynomial quotient
-// As the FRI entry point couls be the quotient polynomial
-// In this basic educational implementation we consider that this method can evaluate the polynomial quotient
-// By segregating the numerator and the denominator and considering the product if any
-pub fn evaluate_on_enlarged_domain(
-    poly: &Polynome<FieldElement>,
-    dom: &Vec<FieldElement>,
-) -> Vec<FieldElement> {
-    return poly.evaluate_sliding(dom);
+// The FRI entry point is often a quotient numerator / denominator, with a vanishing
+// polynomial x^n - c or a point x - z as denominator. The quotient is evaluated on the
+// enlarged domain as the numerator evaluations times the inverses of the denominator
+// evaluations, without computing its coefficients. The division is exact when these
+// evaluations interpolate to a polynomial of degree deg(numerator) - deg(denominator).
+pub fn evaluate_quotient_on_enlarged_domain<F: TwoAdicField, E: ExtensionField<F>>(
+    numerator: &Polynome<E>,
+    denominator: &Polynome<E>,
+    dom: &Domain<F>,
+) -> Result<Vec<E>, QuotientError> {
+    assert!(!denominator.is_zero(), "Division by the zero polynomial");
+    if numerator.coefficients.len() > dom.size() {
+        return Err(QuotientError::DomainTooSmall);
+    }
+
+    let mut denominator_inv = evaluate_on_enlarged_domain(denominator, dom);
+    batch_inverse_in_place(&mut denominator_inv, ZeroPolicy::Error)
+        .map_err(|BatchInversionError::ZeroElement(i)| QuotientError::DenominatorVanishes(i))?;
+
+    let quotient = evaluate_on_enlarged_domain(numerator, dom)
+        .iter()
+        .zip(denominator_inv)
+        .map(|(n, d_inv)| *n * d_inv)
+        .collect::<Vec<E>>();
+
+    let quotient_bound = numerator
+        .coefficients
+        .len()
+        .saturating_sub(denominator.degree().expect("The denominator is not zero"));
+    if Polynome::interpolate_from_domain(&quotient, dom)
+        .coefficients
+        .len()
+        > quotient_bound
+    {
+        return Err(QuotientError::NotDivisible);
+    }
+    Ok(quotient)
 }
 
-pub fn build_next_domain(domain: &Vec<FieldElement>) -> Vec<FieldElement> {
-    let fri_domain = domain.clone();
-    let stop_index = fri_domain.len() / 2;
-    let next_domain = fri_domain
+// DEEP quotient (f(x) - f(z)) / (x - z) on the given points, None if z is one of them
+// It has degree deg(f) - 1 when the value is f(z), the prover folds it instead of f and
+// the verifier recomputes it at the queried points from the openings of f
+pub fn deep_quotient<F: TwoAdicField, E: ExtensionField<F>>(
+    points: &[F],
+    evaluations: &[E],
+    z: &E,
+    value: &E,
+) -> Option<Vec<E>> {
+    let denominators = points
         .iter()
-        .map(|x| x.pow(&[2u64]))
-        .take(stop_index)
-        .collect::<Vec<FieldElement>>();
-    return next_domain;
+        .map(|x| E::from_base(*x) - z)
+        .collect::<Vec<E>>();
+    let denominators_inv = batch_inverse(&denominators, ZeroPolicy::Error).ok()?;
+
+    Some(
+        evaluations
+            .iter()
+            .zip(denominators_inv)
+            .map(|(f, d_inv)| (*f - value) * d_inv)
+            .collect(),
+    )
 }
 
-fn build_merkle_tree(values: &Vec<FieldElement>) -> MerkleTree<Sha256> {
-    let mut leaves: Vec<[u8; 32]> = values
+// Degree-corrected random combination of the values f_j(x) of a batch at x :
+// sum_j alpha^(2j) f_j(x) + alpha^(2j+1) x^(D - 1 - d_j) f_j(x)
+// Every term has degree lower than the degree bound D when deg(f_j) <= d_j
+pub fn batch_combination<F: TwoAdicField, E: ExtensionField<F>>(
+    values: &[E],
+    x: &F,
+    alpha: &E,
+    degrees: &[usize],
+    degree_bound: usize,
+) -> E {
+    let mut combination = E::ZERO;
+    let mut power = E::ONE;
+    for (value, degree) in values.iter().zip(degrees) {
+        let shift = x.pow([(degree_bound - 1 - degree) as u64]);
+        combination += power * value + power * alpha * (*value * shift);
+        power *= alpha.square();
+    }
+    combination
+}
+
+// Degree correction of a quotient with at most `quotient_bound` coefficients up to the
+// degree bound D : q(x) + alpha x^(D - quotient_bound) q(x) has degree lower than D only
+// when q has at most quotient_bound coefficients, so FRI against D tests the quotient bound
+pub fn degree_correction<F: TwoAdicField, E: ExtensionField<F>>(
+    value: &E,
+    x: &F,
+    alpha: &E,
+    quotient_bound: usize,
+    degree_bound: usize,
+) -> E {
+    let shift = x.pow([(degree_bound - quotient_bound) as u64]);
+    *value + *alpha * (*value * shift)
+}
+
+// Zero-knowledge : codeword plus gamma times the mask, of degree lower than the degree
+// bound when both are
+pub fn mask_evaluations<F: TwoAdicField, E: ExtensionField<F>>(
+    evaluations: &[E],
+    mask: &[F],
+    gamma: &E,
+) -> Vec<E> {
+    evaluations
         .iter()
-        .map(|x| {
-            Sha256::hash(
-                x.to_repr()
-                    .as_ref()
-                    .try_into()
-                    .expect("Représentation incorrecte"),
-            )
+        .zip(mask)
+        .map(|(value, r)| *value + *gamma * *r)
+        .collect()
+}
+
+// Next layer domain : the k-th powers of the current domain for a folding factor k
+// The k points x * zeta^j of a coset of the k-th roots of unity give the same power
+// A domain smaller than the folding factor is folded entirely, down to a single point
+pub fn build_next_domain<F: TwoAdicField>(domain: &Domain<F>, folding_factor: usize) -> Domain<F> {
+    assert!(
+        folding_factor >= 2 && folding_factor.is_power_of_two(),
+        "Folding factor should be a power of two"
+    );
+    (0..folding_factor.min(domain.size()).trailing_zeros()).fold(domain.clone(), |d, _| d.square())
+}
+
+// Indices in a layer of size n of the coset opened for a query : the points sharing
+// the k-th power of the query point, at query % (n / k) + j * (n / k) for 0 <= j < k
+// A layer smaller than the folding factor is opened entirely
+pub fn coset_indices(query_index: usize, layer_size: usize, folding_factor: usize) -> Vec<usize> {
+    let coset_size = folding_factor.min(layer_size);
+    let stride = layer_size / coset_size;
+    let base = query_index % stride;
+    (0..coset_size).map(|j| base + j * stride).collect()
+}
+
+// Position of the query point in the coset opened in a layer of size n
+pub fn position_in_coset(query_index: usize, layer_size: usize, folding_factor: usize) -> usize {
+    (query_index % layer_size) / (layer_size / folding_factor.min(layer_size))
+}
+
+// Evaluations of the folded polynomial on the next domain, from the evaluations of f only
+// Folding by k = 2^r is r binary foldings with beta, beta^2, ..., beta^(2^(r-1))
+// As for the domain, a layer smaller than the folding factor is folded entirely
+pub fn fold_evaluations<F: TwoAdicField, E: ExtensionField<F>>(
+    evaluations: &[E],
+    domain: &Domain<F>,
+    beta: &E,
+    folding_factor: usize,
+) -> Vec<E> {
+    let mut evaluations = evaluations.to_vec();
+    let mut domain = domain.clone();
+    let mut beta = *beta;
+    for _ in 0..folding_factor.min(domain.size()).trailing_zeros() {
+        evaluations = fold_evaluations_in_half(&evaluations, &domain, &beta);
+        domain = domain.square();
+        beta = beta.square();
+    }
+    evaluations
+}
+
+// f_next(x^2) = (f(x) + f(-x)) / 2 + beta * (f(x) - f(-x)) / (2x)
+// x = domain[i] and -x = domain[i + n/2] give the evaluation at domain.square()[i]
+fn fold_evaluations_in_half<F: TwoAdicField, E: ExtensionField<F>>(
+    evaluations: &[E],
+    domain: &Domain<F>,
+    beta: &E,
+) -> Vec<E> {
+    assert_eq!(
+        evaluations.len(),
+        domain.size(),
+        "One evaluation per domain element is expected"
+    );
+    let half = domain.size() / 2;
+    let two_inv = Option::<F>::from(F::from(2u64).invert()).expect("The characteristic is odd");
+
+    // 1 / x for the first half of the domain, -x is the second half
+    let x_inv = domain.inverse_first_half();
+
+    (0..half)
+        .map(|i| {
+            let (fx, fmx) = (evaluations[i], evaluations[i + half]);
+            (fx + fmx) * two_inv + *beta * ((fx - fmx) * (two_inv * x_inv[i]))
         })
-        .collect();
+        .collect()
+}
 
-    let mut merkle_tree: MerkleTree<Sha256> = MerkleTree::new();
+// Hash of a single evaluation as stored in the Merkle tree leaves
+pub fn hash_leaf<H: MerkleHasher, F: FriField>(value: &F) -> [u8; 32] {
+    H::hash(&value.to_bytes())
+}
+
+// Leaf hash of an evaluation of the layer, the first layer holds base field elements
+// None if an evaluation of the first layer is not in the base field
+pub fn hash_layer_leaf<H: MerkleHasher, F: FriField, E: ExtensionField<F>>(
+    layer: usize,
+    value: &E,
+) -> Option<[u8; 32]> {
+    if layer == 0 {
+        value.as_base().map(|base| hash_leaf::<H, F>(&base))
+    } else {
+        Some(hash_leaf::<H, E>(value))
+    }
+}
+
+// Hash of a leaf holding several values, a single value gives `hash_leaf`
+pub fn hash_row<H: MerkleHasher, F: FriField>(values: &[F]) -> [u8; 32] {
+    hash_salted_row::<H, F>(values, None)
+}
+
+// Hash of a leaf followed by its random salt in the zero-knowledge mode, so that the
+// hash of a leaf sent in an auth path reveals nothing of its values
+pub fn hash_salted_row<H: MerkleHasher, F: FriField>(
+    values: &[F],
+    salt: Option<&[u8; 32]>,
+) -> [u8; 32] {
+    let mut bytes = values
+        .iter()
+        .flat_map(|v| v.to_bytes())
+        .collect::<Vec<u8>>();
+    if let Some(salt) = salt {
+        bytes.extend_from_slice(salt);
+    }
+    H::hash(&bytes)
+}
+
+// Leaf hash of a row of the layer with its salt if any, the first layer holds base
+// field elements
+pub fn hash_layer_row<H: MerkleHasher, F: FriField, E: ExtensionField<F>>(
+    layer: usize,
+    row: &[E],
+    salt: Option<&[u8; 32]>,
+) -> Option<[u8; 32]> {
+    if layer == 0 {
+        let base = row
+            .iter()
+            .map(|value| value.as_base())
+            .collect::<Option<Vec<F>>>()?;
+        Some(hash_salted_row::<H, F>(&base, salt))
+    } else {
+        Some(hash_salted_row::<H, E>(row, salt))
+    }
+}
+
+pub fn build_merkle_tree<H: MerkleHasher, F: FriField>(values: &[F]) -> MerkleTree<H> {
+    build_layer_merkle_tree(values, 1, 1)
+}
+
+// Merkle leaves of a layer with `width` consecutive values per domain point
+// With a coset size k > 1 the leaf c holds the rows of the points coset_indices(c, n, k),
+// so that a query opens a single leaf, otherwise each row is a leaf
+// The leaf c is salted with salts[c], if any
+pub fn layer_leaves<H: MerkleHasher, F: FriField>(
+    values: &[F],
+    width: usize,
+    coset_size: usize,
+    salts: &[[u8; 32]],
+) -> Vec<[u8; 32]> {
+    let layer_size = values.len() / width;
+    let stride = layer_size / coset_size.min(layer_size);
+    (0..stride)
+        .map(|c| {
+            let leaf = coset_indices(c, layer_size, coset_size)
+                .iter()
+                .flat_map(|i| values[i * width..(i + 1) * width].iter().copied())
+                .collect::<Vec<F>>();
+            hash_salted_row::<H, F>(&leaf, salts.get(c))
+        })
+        .collect()
+}
+
+// One random salt per leaf
+fn random_salts(count: usize) -> Vec<[u8; 32]> {
+    (0..count).map(|_| rand::random()).collect()
+}
+
+// Zero-knowledge : codeword of f + x^shift s for a random s with a coefficient per point
+// opened by the queries, whose values at these points are then uniformly random
+fn blind_codeword<F: TwoAdicField>(
+    codeword: &[F],
+    domain: &Domain<F>,
+    shift: usize,
+    config: &FriConfig,
+) -> Vec<F> {
+    let mut rng = rand::thread_rng();
+    let coefficients = (0..shift + config.zk_opened_points())
+        .map(|i| {
+            if i < shift {
+                F::ZERO
+            } else {
+                F::random(&mut rng)
+            }
+        })
+        .collect::<Vec<F>>();
+    let blinding = evaluate_on_enlarged_domain(&Polynome::new_poly(&coefficients), domain);
+    codeword.iter().zip(blinding).map(|(f, b)| *f + b).collect()
+}
+
+// Codeword of a random masking polynomial, of degree lower than the degree bound of
+// the domain so that the masked codeword keeps the degree of the folding
+fn masking_codeword<F: TwoAdicField>(domain: &Domain<F>, config: &FriConfig) -> Vec<F> {
+    let mut rng = rand::thread_rng();
+    let coefficients = (0..domain.size() / config.blowup_factor)
+        .map(|_| F::random(&mut rng))
+        .collect::<Vec<F>>();
+    evaluate_on_enlarged_domain(&Polynome::new_poly(&coefficients), domain)
+}
+
+// Merkle tree of the leaves of `layer_leaves`
+pub fn build_layer_merkle_tree<H: MerkleHasher, F: FriField>(
+    values: &[F],
+    width: usize,
+    coset_size: usize,
+) -> MerkleTree<H> {
+    let mut leaves = layer_leaves::<H, F>(values, width, coset_size, &[]);
+    let mut merkle_tree: MerkleTree<H> = MerkleTree::new();
 
     merkle_tree.append(&mut leaves);
     merkle_tree.commit();
 
     // Return committed tree
-    return merkle_tree;
+    merkle_tree
 }
 
+// Merkle trees hashed with H
 #[derive(Clone)]
-pub struct FriCodeLayer {
-    pub evaluation: Vec<FieldElement>,
-    pub domain: Vec<FieldElement>,
-    pub merkle_tree: MerkleTree<Sha256>,
+pub struct FriCodeLayer<F: TwoAdicField, E: ExtensionField<F> = F, H: MerkleHasher = Sha256> {
+    // Values of the domain point i : evaluation[i * width..(i + 1) * width]
+    pub evaluation: Vec<E>,
+    // Number of values per domain point, 1 but for a batch layer
+    pub width: usize,
+    // Number of domain points per Merkle leaf, the folding coset size with coset leaves
+    pub coset_size: usize,
+    pub domain: Domain<F>,
+    // Committed by its cap, the root when the cap height is 0
+    pub merkle_tree: MerkleCapTree<H>,
+    // Salt of each leaf in the zero-knowledge mode, empty otherwise
+    pub salts: Vec<[u8; 32]>,
 }
 
-#[derive(Clone)]
-pub struct FriDecommitment {
-    pub layers_evaluations: Vec<FieldElement>,
+// Openings of all the queries, the first layer evaluations are base field elements seen in E
+// Each layer opens the coset of every query point, in the order of `coset_indices`,
+// the auth paths of a layer are merged in a single Merkle multi-proof of all the queried
+// leaves, so the nodes shared between queries are sent once
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriDecommitment<E: FriField, H: MerkleHasher = Sha256> {
+    // queries_evaluations[q][i] : coset evaluations of the query q in the layer i
+    pub queries_evaluations: Vec<Vec<Vec<E>>>,
+    // queries_salts[q][i] : salts of the leaves opened by the query q in the layer i,
+    // empty but in the zero-knowledge mode
+    pub queries_salts: Vec<Vec<Vec<[u8; 32]>>>,
+    // One multi-proof per layer, of the Merkle trees hashed with H
     pub layers_auth_paths: Vec<Vec<[u8; 32]>>,
-    pub layers_evaluations_sym: Vec<FieldElement>,
-    pub layers_auth_paths_sym: Vec<Vec<[u8; 32]>>,
+    hasher: PhantomData<H>,
+}
+
+impl<E: FriField, H: MerkleHasher> FriDecommitment<E, H> {
+    pub fn new(
+        queries_evaluations: Vec<Vec<Vec<E>>>,
+        queries_salts: Vec<Vec<Vec<[u8; 32]>>>,
+        layers_auth_paths: Vec<Vec<[u8; 32]>>,
+    ) -> Self {
+        Self {
+            queries_evaluations,
+            queries_salts,
+            layers_auth_paths,
+            hasher: PhantomData,
+        }
+    }
+
+    // Number of hashes in the auth paths of all the layers
+    pub fn auth_paths_len(&self) -> usize {
+        self.layers_auth_paths.iter().map(Vec::len).sum()
+    }
+}
+
+// Merkle leaves opened by a query in a layer of the given size : the coset of the query
+// point, or the single leaf holding it with coset leaves
+pub fn query_leaves(
+    query_index: usize,
+    layer_size: usize,
+    folding_factor: usize,
+    coset_leaves: bool,
+) -> Vec<usize> {
+    let indices = coset_indices(query_index, layer_size, folding_factor);
+    if coset_leaves {
+        indices[..1].to_vec()
+    } else {
+        indices
+    }
 }
 
-impl FriCodeLayer {
-    pub fn new(poly: &Polynome<FieldElement>, dom: &Vec<FieldElement>) -> Self {
-        let eval = evaluate_on_enlarged_domain(poly, dom);
-        let mtree = build_merkle_tree(&eval);
+impl<F: TwoAdicField, E: ExtensionField<F>, H: MerkleHasher> FriCodeLayer<F, E, H> {
+    pub fn new(poly: &Polynome<E>, dom: &Domain<F>) -> Self {
+        Self::from_evaluations(evaluate_on_enlarged_domain(poly, dom), dom)
+    }
+
+    // First layer : the codeword is committed in the base field
+    pub fn new_base(poly: &Polynome<F>, dom: &Domain<F>) -> Self {
+        Self::from_base_evaluations(evaluate_on_enlarged_domain(poly, dom), dom)
+    }
+
+    pub fn from_evaluations(eval: Vec<E>, dom: &Domain<F>) -> Self {
+        Self::from_rows(eval, 1, 1, 0, false, dom)
+    }
+
+    pub fn from_base_evaluations(eval: Vec<F>, dom: &Domain<F>) -> Self {
+        Self::from_base_rows(eval, 1, 1, 0, false, dom)
+    }
+
+    // Layer with `width` values per domain point and `coset_size` points per leaf,
+    // committed by its Merkle cap of height `cap_height`, with random salts if `salted`
+    pub fn from_rows(
+        rows: Vec<E>,
+        width: usize,
+        coset_size: usize,
+        cap_height: usize,
+        salted: bool,
+        dom: &Domain<F>,
+    ) -> Self {
+        assert_eq!(
+            rows.len(),
+            dom.size() * width,
+            "One row per domain element is expected"
+        );
+        let salts = Self::leaves_salts(coset_size, salted, dom);
+        let mtree = MerkleCapTree::new(
+            &layer_leaves::<H, E>(&rows, width, coset_size, &salts),
+            cap_height,
+        );
 
         Self {
-            evaluation: eval,
-            domain: dom.to_vec(),
+            evaluation: rows,
+            width,
+            coset_size: coset_size.min(dom.size()),
+            domain: dom.clone(),
             merkle_tree: mtree,
+            salts,
+        }
+    }
+
+    // Same in the base field, for the first layer
+    pub fn from_base_rows(
+        rows: Vec<F>,
+        width: usize,
+        coset_size: usize,
+        cap_height: usize,
+        salted: bool,
+        dom: &Domain<F>,
+    ) -> Self {
+        assert_eq!(
+            rows.len(),
+            dom.size() * width,
+            "One row per domain element is expected"
+        );
+        let salts = Self::leaves_salts(coset_size, salted, dom);
+        let mtree = MerkleCapTree::new(
+            &layer_leaves::<H, F>(&rows, width, coset_size, &salts),
+            cap_height,
+        );
+
+        Self {
+            evaluation: rows.into_iter().map(E::from_base).collect(),
+            width,
+            coset_size: coset_size.min(dom.size()),
+            domain: dom.clone(),
+            merkle_tree: mtree,
+            salts,
+        }
+    }
+
+    fn leaves_salts(coset_size: usize, salted: bool, dom: &Domain<F>) -> Vec<[u8; 32]> {
+        if salted {
+            random_salts(dom.size() / coset_size.min(dom.size()))
+        } else {
+            vec![]
         }
     }
 
+    // Values of the domain point
+    pub fn row(&self, index: usize) -> &[E] {
+        &self.evaluation[index * self.width..(index + 1) * self.width]
+    }
+
     pub fn get_merkle_root(&self) -> Option<String> {
-        return self.merkle_tree.root_hex();
+        self.merkle_tree.root().map(hex::encode)
+    }
+
+    // Commitment of the layer sent on the channel, the root without a cap
+    pub fn get_merkle_cap(&self) -> Option<String> {
+        self.merkle_tree.cap_hex()
     }
 
     // Commitment phase
+    // Each round divides the degree bound and the domain size by the folding factor, the
+    // number of rounds only depends on the domain size and the configuration : the degree
+    // bound domain_size / blowup is folded until it is at most the final degree bound.
+    // The last folded polynomial is not committed, it is sent in the clear.
     pub fn fri_commit_phase(
-        initial_poly: Polynome<FieldElement>,
+        initial_poly: Polynome<F>,
         domain_size: usize,
-        interactive_channel: &mut Channel,
-    ) -> (Polynome<FieldElement>, Vec<FriCodeLayer>) {
-        //FieldElement
-        let domain_size = domain_size;
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> (Polynome<E>, Vec<FriCodeLayer<F, E, H>>) {
+        let initial_domain = generate_enlarged_evaluation_domain(domain_size);
+        let evaluations = evaluate_on_enlarged_domain(&initial_poly, &initial_domain);
 
-        let mut fri_layer_list = Vec::with_capacity((initial_poly.degree() / 2) + 1);
+        Self::fri_commit_from_evaluations(evaluations, &initial_domain, config, interactive_channel)
+    }
 
+    // Commitment phase of the quotient numerator / denominator, the domain size is the
+    // one of the quotient degree
+    pub fn fri_commit_quotient(
+        numerator: &Polynome<F>,
+        denominator: &Polynome<F>,
+        domain_size: usize,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> Result<FriCommitment<F, E, H>, QuotientError> {
         let initial_domain = generate_enlarged_evaluation_domain(domain_size);
-        println!(
-            "Initial domain generated with size : {:?}",
-            initial_domain.len()
+        let evaluations =
+            evaluate_quotient_on_enlarged_domain(numerator, denominator, &initial_domain)?;
+
+        Ok(Self::fri_commit_from_evaluations(
+            evaluations,
+            &initial_domain,
+            config,
+            interactive_channel,
+        ))
+    }
+
+    // Commitment phase from the codeword only : each layer is folded from the evaluations
+    // of the previous one, the final polynomial is interpolated from the last evaluations
+    // The domain should be the one of the verifier, `generate_enlarged_evaluation_domain`
+    // In the DEEP mode the first layer still commits f, the folding starts from its quotient
+    pub fn fri_commit_from_evaluations(
+        evaluations: Vec<F>,
+        domain: &Domain<F>,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> (Polynome<E>, Vec<FriCodeLayer<F, E, H>>) {
+        Self::fri_commit_with_quotient(evaluations, domain, None, config, interactive_channel)
+    }
+
+    // Commitment phase of the opening of f of degree at most max_degree at z to value :
+    // the first layer commits f, the folding starts from the quotient
+    // (f(x) - value) / (x - z), tested against the degree bound max_degree - 1
+    // A point of the domain cannot be opened, nothing is sent on the channel then
+    // An opening reveals f(z), it has no zero-knowledge mode
+    pub fn fri_commit_opening(
+        evaluations: Vec<F>,
+        domain: &Domain<F>,
+        point: &E,
+        value: &E,
+        max_degree: usize,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> Result<FriCommitment<F, E, H>, QuotientError> {
+        assert!(
+            max_degree < domain.size() / config.blowup_factor,
+            "Polynomial degree exceeds the domain degree bound"
         );
+        assert!(
+            !config.zero_knowledge,
+            "An opening has no zero-knowledge mode"
+        );
+        if let Some(i) = domain
+            .elements()
+            .iter()
+            .position(|x| E::from_base(*x) == *point)
+        {
+            return Err(QuotientError::DenominatorVanishes(i));
+        }
 
-        let mut current_layer = FriCodeLayer::new(&initial_poly, &initial_domain);
-        println!("Initial layer generated");
+        Ok(Self::fri_commit_with_quotient(
+            evaluations,
+            domain,
+            Some((*point, *value, max_degree)),
+            config,
+            interactive_channel,
+        ))
+    }
 
-        fri_layer_list.push(current_layer.clone());
+    // The opening point, if any, replaces the DEEP point, with the number of coefficients
+    // of its quotient
+    fn fri_commit_with_quotient(
+        evaluations: Vec<F>,
+        domain: &Domain<F>,
+        opening: Option<(E, E, usize)>,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> (Polynome<E>, Vec<FriCodeLayer<F, E, H>>) {
+        config.validate().expect("Invalid FRI configuration");
+        assert_eq!(
+            evaluations.len(),
+            domain.size(),
+            "One evaluation per domain element is expected"
+        );
+        let number_of_rounds = config.number_of_rounds(domain.size());
 
-        let mut current_poly = initial_poly;
-        let mut current_domain = initial_domain;
+        let mut fri_layer_list = Vec::with_capacity(number_of_rounds.max(1));
+
+        // In the zero-knowledge mode f is blinded by random coefficients at the top of the
+        // degree bound, `FriConfig::domain_size` leaves room for them above its degree
+        let degree_bound = domain.size() / config.blowup_factor;
+        let evaluations = if config.zero_knowledge {
+            let blinding_shift = degree_bound
+                .checked_sub(config.zk_opened_points())
+                .expect("The degree bound leaves no room to blind the polynomial");
+            blind_codeword(&evaluations, domain, blinding_shift, config)
+        } else {
+            evaluations
+        };
+
+        // In the zero-knowledge mode the row of a point holds f(x) then the mask r(x)
+        let mask = config
+            .zero_knowledge
+            .then(|| masking_codeword(domain, config));
+        let (rows, width) = match &mask {
+            Some(mask) => (
+                evaluations
+                    .iter()
+                    .zip(mask)
+                    .flat_map(|(f, r)| [*f, *r])
+                    .collect(),
+                2,
+            ),
+            None => (evaluations, 1),
+        };
+        let current_layer = Self::from_base_rows(
+            rows,
+            width,
+            config.leaf_coset_size(),
+            config.merkle_cap_height,
+            config.zero_knowledge,
+            domain,
+        );
 
         // >>>> Send commitment root
         // For the initial polynome we consider to map the merckle root to the 0 field element
-        interactive_channel.add_committed_data(
-            FieldElement::from(0u64),
-            current_layer.merkle_tree.root_hex(),
+        interactive_channel.add_committed_data(E::ZERO, current_layer.get_merkle_cap());
+
+        // The folding with the first beta moves the codeword to the extension
+        let mut current_evaluation = (0..domain.size())
+            .map(|i| current_layer.row(i)[0])
+            .collect::<Vec<E>>();
+        fri_layer_list.push(current_layer);
+
+        // The quotient has one coefficient less than f
+        let quotient_point = match opening {
+            Some((point, value, max_degree)) => {
+                // >>>> Send the opening point and the claimed value
+                interactive_channel.add_opening(point, value);
+                Some((point, value, max_degree))
+            }
+            None if config.deep => {
+                // <<<< Receive the out of domain point
+                let z = interactive_channel.get_deep_point();
+
+                // >>>> Send f(z)
+                let f = Polynome::interpolate_from_domain(&current_evaluation, domain);
+                let value = f.evaluate(&z);
+                interactive_channel.add_deep_evaluation(value);
+                Some((z, value, degree_bound - 1))
+            }
+            None => None,
+        };
+
+        // Fold the quotient by x - z instead of f
+        if let Some((z, value, quotient_bound)) = quotient_point {
+            // An opening point out of the domain is checked beforehand, a DEEP point is
+            // out of it but with a negligible probability
+            current_evaluation = deep_quotient(domain.elements(), &current_evaluation, &z, &value)
+                .expect("The quotient point should be out of the domain");
+
+            // The degree correction of the quotient is tested against the degree bound
+            // <<<< Receive the degree correction challenge
+            let alpha = interactive_channel.get_degree_correction_challenge();
+            current_evaluation = current_evaluation
+                .iter()
+                .zip(domain.elements())
+                .map(|(q, x)| degree_correction(q, x, &alpha, quotient_bound, degree_bound))
+                .collect();
+        }
+
+        if let Some(mask) = mask {
+            // <<<< Receive the masking challenge
+            let gamma = interactive_channel.get_mask_challenge();
+            current_evaluation = mask_evaluations(&current_evaluation, &mask, &gamma);
+        }
+
+        let last_poly = Self::fri_fold_phase(
+            current_evaluation,
+            domain,
+            config,
+            interactive_channel,
+            &mut fri_layer_list,
         );
 
-        while current_poly.degree() > 0 {
-            println!(
-                "Generate new layer with polynome degree : {:?}",
-                current_poly.degree()
-            );
-            // <<<< Receive challenge
-            let beta_challenge = interactive_channel.get_challenge();
+        (last_poly, fri_layer_list)
+    }
 
-            // Compute layer polynomial and domain
-            // Next poly
-            let next_poly = current_poly.fold_with_beta(&beta_challenge);
-            // Next domain
-            let next_domain = build_next_domain(&current_domain);
+    // Batch commitment phase : the codewords of the polynomials are committed together,
+    // the leaf of a domain point holds the evaluations of every polynomial at this point.
+    // The folding starts from their degree-corrected random combination, of degree lower
+    // than the degree bound domain_size / blowup when deg(polys[j]) <= polys[j].degree()
+    // The DEEP mode does not apply to batches
+    // In the zero-knowledge mode each polynomial is blinded by zk_opened_points random
+    // coefficients above its degree, and its degree bound is raised by as many
+    pub fn fri_commit_batch(
+        polys: &[Polynome<F>],
+        domain_size: usize,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+    ) -> FriCommitment<F, E, H> {
+        config.validate().expect("Invalid FRI configuration");
+        assert!(!polys.is_empty(), "The batch should not be empty");
+        let degree_bound = domain_size / config.blowup_factor;
+        // A zero polynomial is batched with degree 0
+        let degrees = polys
+            .iter()
+            .map(|p| p.degree().unwrap_or(0) + config.zk_opened_points())
+            .collect::<Vec<usize>>();
+        assert!(
+            degrees.iter().all(|d| *d < degree_bound),
+            "Polynomial degree exceeds the domain degree bound"
+        );
+
+        let domain = generate_enlarged_evaluation_domain(domain_size);
+        let codewords = polys
+            .iter()
+            .map(|p| {
+                let codeword = evaluate_on_enlarged_domain(p, &domain);
+                if config.zero_knowledge {
+                    blind_codeword(&codeword, &domain, p.coefficients.len(), config)
+                } else {
+                    codeword
+                }
+            })
+            .collect::<Vec<Vec<F>>>();
+        // In the zero-knowledge mode the mask r(x) ends the row of each point
+        let mask = config
+            .zero_knowledge
+            .then(|| masking_codeword(&domain, config));
+        let rows = (0..domain_size)
+            .flat_map(|i| {
+                codewords
+                    .iter()
+                    .map(move |codeword| codeword[i])
+                    .chain(mask.as_ref().map(|mask| mask[i]))
+            })
+            .collect::<Vec<F>>();
+
+        let batch_layer = Self::from_base_rows(
+            rows,
+            polys.len() + mask.is_some() as usize,
+            config.leaf_coset_size(),
+            config.merkle_cap_height,
+            config.zero_knowledge,
+            &domain,
+        );
+
+        // >>>> Send commitment root
+        interactive_channel.add_committed_data(E::ZERO, batch_layer.get_merkle_cap());
+
+        // <<<< Receive the combination challenge
+        let alpha = interactive_channel.get_batch_challenge();
+        let mut combination = (0..domain_size)
+            .map(|i| {
+                batch_combination(
+                    &batch_layer.row(i)[..polys.len()],
+                    &domain[i],
+                    &alpha,
+                    &degrees,
+                    degree_bound,
+                )
+            })
+            .collect::<Vec<E>>();
+
+        if let Some(mask) = mask {
+            // <<<< Receive the masking challenge
+            let gamma = interactive_channel.get_mask_challenge();
+            combination = mask_evaluations(&combination, &mask, &gamma);
+        }
+
+        let mut fri_layer_list = vec![batch_layer];
+        let last_poly = Self::fri_fold_phase(
+            combination,
+            &domain,
+            config,
+            interactive_channel,
+            &mut fri_layer_list,
+        );
+
+        (last_poly, fri_layer_list)
+    }
 
-            // Compute next layer
-            current_layer = FriCodeLayer::new(&next_poly, &next_domain);
+    // Folding rounds from the codeword on the initial domain, the folded layers are
+    // committed and pushed to the list, the final polynomial is returned
+    fn fri_fold_phase(
+        mut current_evaluation: Vec<E>,
+        domain: &Domain<F>,
+        config: &FriConfig,
+        interactive_channel: &mut Channel<E, H>,
+        fri_layer_list: &mut Vec<FriCodeLayer<F, E, H>>,
+    ) -> Polynome<E> {
+        let folding_factor = config.folding_factor;
+        let number_of_rounds = config.number_of_rounds(domain.size());
+        let mut current_domain = domain.clone();
 
-            // >>>> Send commitment root
-            interactive_channel.add_committed_data(beta_challenge, current_layer.get_merkle_root());
-            println!(
-                "Commitment root: {}",
-                current_layer.get_merkle_root().unwrap_or_default()
+        for round in 0..number_of_rounds {
+            // <<<< Receive challenge
+            let beta_challenge = interactive_channel.get_challenge();
+
+            // Compute layer evaluations and domain
+            current_evaluation = fold_evaluations(
+                &current_evaluation,
+                &current_domain,
+                &beta_challenge,
+                folding_factor,
             );
+            current_domain = build_next_domain(&current_domain, folding_factor);
 
-            fri_layer_list.push(current_layer);
+            // The last round gives the final polynomial, no layer is committed for it
+            if round + 1 < number_of_rounds {
+                let current_layer = Self::from_rows(
+                    current_evaluation.clone(),
+                    1,
+                    config.leaf_coset_size(),
+                    config.merkle_cap_height,
+                    config.zero_knowledge,
+                    &current_domain,
+                );
 
-            // Update current values
+                // >>>> Send commitment root
+                interactive_channel
+                    .add_committed_data(beta_challenge, current_layer.get_merkle_cap());
 
-            current_poly = next_poly;
-            current_domain = next_domain;
+                fri_layer_list.push(current_layer);
+            }
         }
 
-        let last_poly = current_poly;
+        let last_poly = Polynome::interpolate_from_domain(&current_evaluation, &current_domain);
 
-        return (last_poly, fri_layer_list);
+        // >>>> Send the final polynomial in the clear
+        interactive_channel.add_final_polynomial(&last_poly.coefficients);
+
+        last_poly
     }
 
     // Decommitment phase
+    // The proof of work, if any, is done before the queries are derived
     pub fn fri_decommitment_phase(
-        fri_number_of_queries: i32,
+        config: &FriConfig,
         domain_size: usize,
-        fri_layers: &Vec<FriCodeLayer>,
-        i_channel: &mut Channel,
-    ) -> (Vec<FriDecommitment>, Vec<usize>) {
+        fri_layers: &[FriCodeLayer<F, E, H>],
+        i_channel: &mut Channel<E, H>,
+    ) -> (FriDecommitment<E, H>, Vec<usize>) {
+        config.validate().expect("Invalid FRI configuration");
+        let folding_factor = config.folding_factor;
+
         if !fri_layers.is_empty() {
-            let coef_index_queries = (0..fri_number_of_queries)
+            if config.grinding_bits > 0 {
+                i_channel.grind(config.grinding_bits);
+            }
+
+            let coef_index_queries = (0..config.number_of_queries)
                 .map(|_| (i_channel.get_index()) % domain_size)
                 .collect::<Vec<usize>>();
 
-            let query_list = coef_index_queries
+            let queries_evaluations = coef_index_queries
                 .iter()
                 .map(|i| {
                     // <<<< Receive challenge index
-                    let mut layers_evaluations = vec![];
-                    let mut layers_auth_paths = vec![];
-                    let mut layers_evaluations_sym = vec![];
-                    let mut layers_auth_paths_sym = vec![];
-
-                    for layer in fri_layers {
-                        // symmetric element
-                        let dom_size = layer.domain.len();
-
-                        let index = i % dom_size;
-                        let index_sym = (i + dom_size / 2) % dom_size;
-
-                        let evaluation = layer.evaluation[index].clone();
-                        let auth_path = layer.merkle_tree.proof(&[index]);
-                        let auth_path_hashes = auth_path.proof_hashes();
-
-                        let evaluation_sym = layer.evaluation[index_sym].clone();
-                        let auth_path_sym = layer.merkle_tree.proof(&[index_sym]);
-                        let auth_path_hashes_sym = auth_path_sym.proof_hashes();
-
-                        layers_evaluations.push(evaluation);
-                        layers_auth_paths.push(auth_path_hashes.to_vec());
-                        layers_evaluations_sym.push(evaluation_sym);
-                        layers_auth_paths_sym.push(auth_path_hashes_sym.to_vec());
-                    }
-
-                    FriDecommitment {
-                        layers_evaluations,
-                        layers_auth_paths,
-                        layers_evaluations_sym,
-                        layers_auth_paths_sym,
-                    }
+                    fri_layers
+                        .iter()
+                        .map(|layer| {
+                            // Coset of the query point, the k values folded together
+                            coset_indices(*i, layer.domain.size(), folding_factor)
+                                .iter()
+                                .flat_map(|index| layer.row(*index).iter().copied())
+                                .collect::<Vec<E>>()
+                        })
+                        .collect()
+                })
+                .collect();
+
+            let queries_salts = coef_index_queries
+                .iter()
+                .map(|i| {
+                    fri_layers
+                        .iter()
+                        .map(|layer| {
+                            if layer.salts.is_empty() {
+                                return vec![];
+                            }
+                            query_leaves(
+                                *i,
+                                layer.domain.size(),
+                                folding_factor,
+                                layer.coset_size > 1,
+                            )
+                            .iter()
+                            .map(|leaf| layer.salts[*leaf])
+                            .collect()
+                        })
+                        .collect()
                 })
                 .collect();
 
-            (query_list, coef_index_queries)
+            let layers_auth_paths = fri_layers
+                .iter()
+                .map(|layer| {
+                    let mut leaves = coef_index_queries
+                        .iter()
+                        .flat_map(|i| {
+                            query_leaves(
+                                *i,
+                                layer.domain.size(),
+                                folding_factor,
+                                layer.coset_size > 1,
+                            )
+                        })
+                        .collect::<Vec<usize>>();
+                    leaves.sort_unstable();
+                    leaves.dedup();
+                    layer.merkle_tree.proof(&leaves)
+                })
+                .collect();
+
+            let decommitment =
+                FriDecommitment::new(queries_evaluations, queries_salts, layers_auth_paths);
+
+            (decommitment, coef_index_queries)
         } else {
-            (vec![], vec![])
+            (FriDecommitment::new(vec![], vec![], vec![]), vec![])
         }
     }
+
+    // Number of hashes the auth paths would take with one auth path per query and per
+    // layer, to compare with the multi-proofs of `fri_decommitment_phase`
+    pub fn per_query_auth_paths_len(
+        config: &FriConfig,
+        fri_layers: &[FriCodeLayer<F, E, H>],
+        queries: &[usize],
+    ) -> usize {
+        queries
+            .iter()
+            .flat_map(|i| {
+                fri_layers.iter().map(|layer| {
+                    let leaves = query_leaves(
+                        *i,
+                        layer.domain.size(),
+                        config.folding_factor,
+                        layer.coset_size > 1,
+                    );
+                    layer.merkle_tree.proof(&leaves).len()
+                })
+            })
+            .sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
+    use ff::Field;
     use rs_merkle::MerkleProof;
 
     use super::*;
+    use crate::field_extension::QuadraticExtension;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+    use ff::PrimeField;
 
     #[test]
     fn test_generate_enlarged_evaluation_domain() {
-        let domain_size = 5;
-        let result = generate_enlarged_evaluation_domain(domain_size);
-        assert_eq!(
-            result,
-            vec![
-                FieldElement::from(7u64),
-                FieldElement::from(343u64),
-                FieldElement::from(16807u64),
-                FieldElement::from(823543u64),
-                FieldElement::from(40353607u64),
-            ]
-        );
+        let domain_size = 4;
+        let result = generate_enlarged_evaluation_domain::<FieldElement>(domain_size);
+        let g = FieldElement::MULTIPLICATIVE_GENERATOR;
+        let w = result.generator();
+
+        assert_eq!(w.pow([4u64]), FieldElement::from(1u64));
+        assert_eq!(w.pow([2u64]), -FieldElement::from(1u64));
+        assert_eq!(result.elements(), &[g, g * w, g * w * w, g * w * w * w]);
     }
 
     #[test]
@@ -255,29 +1038,13 @@ mod tests {
             FieldElement::from(3u64),
         ];
         let p = Polynome::new_poly(&coefficients);
-        let domain_size = 5;
+        let domain_size = 4;
         let dom = generate_enlarged_evaluation_domain(domain_size);
         let eval = evaluate_on_enlarged_domain(&p, &dom);
-        assert_eq!(
-            eval,
-            vec![
-                FieldElement::from(162u64),
-                FieldElement::from(353634u64),
-                FieldElement::from(847459362u64),
-                FieldElement::from(2034670865634u64),
-                FieldElement::from(4885240874438562u64),
-            ]
-        );
-        assert_eq!(
-            dom,
-            vec![
-                FieldElement::from(7u64),
-                FieldElement::from(343u64),
-                FieldElement::from(16807u64),
-                FieldElement::from(823543u64),
-                FieldElement::from(40353607u64),
-            ]
-        );
+
+        // 1 + 2x + 3x^2 at x = 7
+        assert_eq!(eval[0], FieldElement::from(162u64));
+        (0..domain_size).for_each(|i| assert_eq!(eval[i], p.evaluate(&dom[i])));
     }
 
     #[test]
@@ -291,7 +1058,7 @@ mod tests {
             FieldElement::from(6u64),
         ];
 
-        let merkle_tree = build_merkle_tree(&values);
+        let merkle_tree = build_merkle_tree::<Sha256, _>(&values);
 
         assert_eq!(
             merkle_tree.root_hex(),
@@ -300,42 +1067,63 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Symmetry should be respected")]
     fn test_eval_domain_symetry() {
-        let domain_size = 10000;
-        let domain = generate_enlarged_evaluation_domain(domain_size);
-        let half_domain_size = domain.len() / 2; // Auto flooring
+        let domain_size = 8192;
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(domain_size);
+        let half_domain_size = domain.size() / 2;
 
         assert_eq!(
-            domain[100].pow(&[2u64]),
-            domain[half_domain_size + 100].pow(&[2u64])
-        ); //Issue on the domain generation to investigate
+            domain[100].pow([2u64]),
+            domain[half_domain_size + 100].pow([2u64]),
+            "Symmetry should be respected"
+        );
+        assert_eq!(domain[half_domain_size + 100], -domain[100]);
     }
 
     #[test]
     fn test_build_next_domain() {
-        let domain = vec![
-            FieldElement::from(1u64),
-            FieldElement::from(2u64),
-            FieldElement::from(3u64),
-            FieldElement::from(4u64),
-            FieldElement::from(5u64),
-            FieldElement::from(6u64),
-            FieldElement::from(7u64),
-            FieldElement::from(8u64),
-        ];
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(8);
 
-        let result = build_next_domain(&domain);
+        let result = build_next_domain(&domain, 2);
 
+        assert_eq!(result.size(), 4);
         assert_eq!(
-            result,
-            vec![
-                FieldElement::from(1u64),
-                FieldElement::from(4u64),
-                FieldElement::from(9u64),
-                FieldElement::from(16u64),
+            result.elements(),
+            &[
+                domain[0].pow([2u64]),
+                domain[1].pow([2u64]),
+                domain[2].pow([2u64]),
+                domain[3].pow([2u64]),
             ]
         );
+        assert_eq!(
+            result,
+            Domain::new_coset(4, FieldElement::MULTIPLICATIVE_GENERATOR.square())
+        );
+
+        let result = build_next_domain(&domain, 8);
+        assert_eq!(
+            result,
+            Domain::new_coset(1, FieldElement::MULTIPLICATIVE_GENERATOR.pow([8u64]))
+        );
+        assert_eq!(build_next_domain(&domain, 16), result);
+    }
+
+    #[test]
+    fn test_coset_indices() {
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(16);
+        let next_domain = build_next_domain(&domain, 4);
+
+        let indices = coset_indices(13, 16, 4);
+        assert_eq!(indices, vec![1, 5, 9, 13]);
+        assert_eq!(position_in_coset(13, 16, 4), 3);
+        indices
+            .iter()
+            .for_each(|i| assert_eq!(domain[*i].pow([4u64]), next_domain[1]));
+
+        // Layer smaller than the folding factor
+        assert_eq!(coset_indices(13, 2, 4), vec![0, 1]);
+        assert_eq!(position_in_coset(13, 2, 4), 1);
     }
 
     #[test]
@@ -350,12 +1138,18 @@ mod tests {
             FieldElement::from(3u64),
         ];
         let poly = Polynome::new_poly(&coefficients);
-        let domain_size = 48; // 8 time degree of the polynome
-        let i_channel = &mut Channel::new();
-        let (last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(poly, domain_size, i_channel);
+        let domain_size = 64; // 8 time degree of the polynome, rounded to a power of two
+        let i_channel = &mut Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_phase(
+            poly,
+            domain_size,
+            &FriConfig::default(),
+            i_channel,
+        );
 
-        assert_eq!(fri_layers.len(), 4);
-        assert_eq!(last_poly.degree(), 0);
+        // Degree bounds 8, 4, 2 committed, the final polynomial is constant
+        assert_eq!(fri_layers.len(), 3);
+        assert_eq!(last_poly.degree(), Some(0));
     }
 
     #[test]
@@ -370,35 +1164,378 @@ mod tests {
             FieldElement::from(3u64),
         ];
         let poly = Polynome::new_poly(&coefficients);
-        let domain_size = 48; // 8 time degree of the polynome
-        let i_channel = &mut Channel::new();
-        let (last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(poly, domain_size, i_channel);
+        let domain_size = 64; // 8 time degree of the polynome, rounded to a power of two
+        let i_channel = &mut Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_phase(
+            poly,
+            domain_size,
+            &FriConfig::default(),
+            i_channel,
+        );
 
+        let config = FriConfig {
+            number_of_queries: 3,
+            ..FriConfig::default()
+        };
         let (decom, queries) =
-            FriCodeLayer::fri_decommitment_phase(3, domain_size, &fri_layers, i_channel);
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, i_channel);
 
-        assert_eq!(last_poly.degree(), 0);
-        assert_eq!(decom.len(), 3);
+        assert_eq!(last_poly.degree(), Some(0));
+        assert_eq!(decom.queries_evaluations.len(), 3);
         assert_eq!(queries.len(), 3);
-        decom.iter().for_each(|d| {
-            assert_eq!(d.layers_evaluations.len(), 4);
-            assert_eq!(d.layers_auth_paths.len(), 4);
-            assert_eq!(d.layers_evaluations_sym.len(), 4);
-            assert_eq!(d.layers_auth_paths_sym.len(), 4);
-
-            (0..4).for_each(|i| {
-                let proof_hashes = d.layers_auth_paths[i].clone();
-                let proof = MerkleProof::<Sha256>::new(proof_hashes);
-
-                let eval_hash = Sha256::hash(
-                    d.layers_evaluations[i]
-                        .to_repr()
-                        .as_ref()
-                        .try_into()
-                        .expect("Représentation incorrecte"),
-                );
-                assert_eq!(hex::encode(eval_hash), proof.proof_hashes_hex()[0]);
-            });
+        assert_eq!(decom.layers_auth_paths.len(), 3);
+        decom
+            .queries_evaluations
+            .iter()
+            .for_each(|evaluations| assert_eq!(evaluations.len(), 3));
+
+        // One multi-proof per layer for the cosets of all the queries
+        (0..3).for_each(|i| {
+            let proof = MerkleProof::<Sha256>::new(decom.layers_auth_paths[i].clone());
+            let dom_size = fri_layers[i].domain.size();
+            let mut leaves = queries
+                .iter()
+                .zip(&decom.queries_evaluations)
+                .flat_map(|(query, evaluations)| {
+                    coset_indices(*query, dom_size, 2)
+                        .into_iter()
+                        .zip(evaluations[i].iter().map(hash_leaf::<Sha256, _>))
+                })
+                .collect::<Vec<(usize, [u8; 32])>>();
+            leaves.sort();
+            leaves.dedup();
+            let (indices, eval_hashes): (Vec<usize>, Vec<[u8; 32]>) = leaves.into_iter().unzip();
+            let root = fri_layers[i].merkle_tree.root().unwrap();
+            assert!(proof.verify(root, &indices, &eval_hashes, dom_size));
         });
     }
+
+    #[test]
+    fn test_fri_phases_with_folding_factor() {
+        let coefficients = (1..=32)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let poly = Polynome::new_poly(&coefficients);
+        let domain_size = 256;
+        let i_channel = &mut Channel::<FieldElement>::new();
+        let config = FriConfig {
+            number_of_queries: 2,
+            folding_factor: 8,
+            ..FriConfig::default()
+        };
+        let (last_poly, fri_layers) =
+            FriCodeLayer::<_, _, Sha256>::fri_commit_phase(poly, domain_size, &config, i_channel);
+
+        // Degrees 31, 3 on domains of size 256, 32, then a constant final polynomial
+        assert_eq!(fri_layers.len(), 2);
+        assert_eq!(fri_layers[1].domain.size(), 32);
+        assert_eq!(last_poly.degree(), Some(0));
+
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, i_channel);
+        decom.queries_evaluations.iter().for_each(|evaluations| {
+            assert_eq!(
+                evaluations.iter().map(Vec::len).collect::<Vec<usize>>(),
+                vec![8, 8]
+            );
+        });
+        (0..2).for_each(|i| {
+            let dom_size = fri_layers[i].domain.size();
+            let mut leaves = queries
+                .iter()
+                .zip(&decom.queries_evaluations)
+                .flat_map(|(query, evaluations)| {
+                    coset_indices(*query, dom_size, 8)
+                        .into_iter()
+                        .zip(evaluations[i].iter().map(hash_leaf::<Sha256, _>))
+                })
+                .collect::<Vec<(usize, [u8; 32])>>();
+            leaves.sort();
+            leaves.dedup();
+            let (indices, eval_hashes): (Vec<usize>, Vec<[u8; 32]>) = leaves.into_iter().unzip();
+            let proof = MerkleProof::<Sha256>::new(decom.layers_auth_paths[i].clone());
+            let root = fri_layers[i].merkle_tree.root().unwrap();
+            assert!(proof.verify(root, &indices, &eval_hashes, dom_size));
+        });
+    }
+
+    #[test]
+    fn test_multi_proof_size() {
+        let poly = Polynome::new_poly(&(1..=16).map(Goldilocks::from).collect::<Vec<Goldilocks>>());
+        let domain_size = 128;
+
+        for coset_leaves in [false, true] {
+            let config = FriConfig {
+                number_of_queries: 40,
+                coset_leaves,
+                ..FriConfig::default()
+            };
+            let i_channel = &mut Channel::<Goldilocks>::new();
+            let (_, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_phase(
+                poly.clone(),
+                domain_size,
+                &config,
+                i_channel,
+            );
+            let (decom, queries) =
+                FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, i_channel);
+
+            // Many queries on small layers share most of their auth path nodes
+            let per_query = FriCodeLayer::per_query_auth_paths_len(&config, &fri_layers, &queries);
+            assert_eq!(decom.layers_auth_paths.len(), fri_layers.len());
+            assert!(2 * decom.auth_paths_len() < per_query);
+        }
+    }
+
+    #[test]
+    fn test_fold_evaluations() {
+        type E = QuadraticExtension<Goldilocks>;
+        let poly = Polynome::new_poly(&(1..=30).map(Goldilocks::from).collect::<Vec<Goldilocks>>());
+        let domain = generate_enlarged_evaluation_domain::<Goldilocks>(64);
+        let evaluations = poly.to_extension::<E>().evaluate_over_domain(&domain);
+        let beta = E::x() + E::from(5u64);
+
+        for folding_factor in [2, 4, 8, 16] {
+            let next_domain = build_next_domain(&domain, folding_factor);
+            assert_eq!(
+                fold_evaluations(&evaluations, &domain, &beta, folding_factor),
+                poly.fold_with_factor(&beta, folding_factor)
+                    .evaluate_over_domain(&next_domain)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fold_layer_smaller_than_folding_factor() {
+        let poly = Polynome::new_poly(&(1..=3).map(Goldilocks::from).collect::<Vec<Goldilocks>>());
+        let domain = generate_enlarged_evaluation_domain::<Goldilocks>(8);
+        let evaluations = poly.evaluate_over_domain(&domain);
+        let beta = Goldilocks::from(7u64);
+
+        // Folding by 16 folds the 8 points by 8, down to a single point
+        let next_domain = build_next_domain(&domain, 16);
+        assert_eq!(next_domain.size(), 1);
+        assert_eq!(
+            fold_evaluations(&evaluations, &domain, &beta, 16),
+            fold_evaluations(&evaluations, &domain, &beta, 8)
+        );
+        assert_eq!(
+            fold_evaluations(&evaluations, &domain, &beta, 16),
+            poly.fold_with_factor(&beta, 8)
+                .evaluate_over_domain(&next_domain)
+        );
+    }
+
+    #[test]
+    fn test_fri_commit_from_evaluations() {
+        let coefficients = (1..=16).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let poly = Polynome::new_poly(&coefficients);
+        let config = FriConfig {
+            folding_factor: 4,
+            final_degree_bound: 2,
+            ..FriConfig::default()
+        };
+        let domain_size = config.domain_size(poly.degree().unwrap());
+
+        // The codeword alone gives the same commitments as the polynomial
+        let domain = generate_enlarged_evaluation_domain(domain_size);
+        let evaluations = evaluate_on_enlarged_domain(&poly, &domain);
+        let mut channel = Channel::<QuadraticExtension<Goldilocks>>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_from_evaluations(
+            evaluations,
+            &domain,
+            &config,
+            &mut channel,
+        );
+
+        let mut expected_channel = Channel::<QuadraticExtension<Goldilocks>>::new();
+        let mut current_poly = poly.to_extension();
+        let mut current_domain = domain;
+        expected_channel.add_committed_data(
+            QuadraticExtension::ZERO,
+            build_merkle_tree::<Sha256, _>(&evaluate_on_enlarged_domain(&poly, &current_domain))
+                .root_hex(),
+        );
+        // Degree bounds 16, 4 committed, the final polynomial has degree lower than 2
+        for round in 0..2 {
+            let beta = expected_channel.get_challenge();
+            current_poly = current_poly.fold_with_factor(&beta, 4);
+            current_domain = build_next_domain(&current_domain, 4);
+            if round == 0 {
+                let layer = FriCodeLayer::<_, _, Sha256>::new(&current_poly, &current_domain);
+                expected_channel.add_committed_data(beta, layer.get_merkle_root());
+            }
+        }
+
+        assert_eq!(fri_layers.len(), 2);
+        assert_eq!(
+            channel.get_committed_roots(),
+            expected_channel.get_committed_roots()
+        );
+        assert_eq!(last_poly.coefficients, current_poly.coefficients);
+        assert!(last_poly.coefficients.len() <= 2);
+    }
+
+    #[test]
+    fn test_evaluate_quotient() {
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(64);
+        let quotient = Polynome::new_poly(
+            &(1..=7)
+                .map(|i| FieldElement::from(i * 3))
+                .collect::<Vec<FieldElement>>(),
+        );
+
+        // Subgroup vanishing polynomial x^8 - 1
+        let vanishing = Polynome::subgroup_vanishing_polynomial(8, &FieldElement::ONE);
+        // Point x - z
+        let z = FieldElement::from(11u64);
+        let point = Polynome::new_poly(&[-z, FieldElement::ONE]);
+
+        for denominator in [&vanishing, &point] {
+            let numerator = &quotient * denominator;
+            assert_eq!(
+                evaluate_quotient_on_enlarged_domain(&numerator, denominator, &domain),
+                Ok(evaluate_on_enlarged_domain(&quotient, &domain))
+            );
+
+            let not_divisible = &numerator + &Polynome::new_poly(&[FieldElement::ONE]);
+            assert_eq!(
+                evaluate_quotient_on_enlarged_domain(&not_divisible, denominator, &domain),
+                Err(QuotientError::NotDivisible)
+            );
+        }
+
+        // x - z with z in the domain
+        let in_domain = Polynome::new_poly(&[-domain[5], FieldElement::ONE]);
+        assert_eq!(
+            evaluate_quotient_on_enlarged_domain(&(&quotient * &in_domain), &in_domain, &domain),
+            Err(QuotientError::DenominatorVanishes(5))
+        );
+
+        let small_domain = generate_enlarged_evaluation_domain::<FieldElement>(4);
+        assert_eq!(
+            evaluate_quotient_on_enlarged_domain(&(&quotient * &point), &point, &small_domain),
+            Err(QuotientError::DomainTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_fri_commit_quotient() {
+        let quotient = Polynome::new_poly(
+            &(1..=8)
+                .map(|i| FieldElement::from(i * i))
+                .collect::<Vec<FieldElement>>(),
+        );
+        let denominator = Polynome::new_poly(&[-FieldElement::from(4u64), FieldElement::ONE]);
+        let numerator = &quotient * &denominator;
+        let config = FriConfig::default();
+        let domain_size = config.domain_size(quotient.degree().unwrap());
+
+        // Same commitments as the quotient polynomial itself
+        let channel = &mut Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_quotient(
+            &numerator,
+            &denominator,
+            domain_size,
+            &config,
+            channel,
+        )
+        .unwrap();
+
+        let expected_channel = &mut Channel::<FieldElement>::new();
+        let (expected_last_poly, expected_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_phase(
+            quotient,
+            domain_size,
+            &config,
+            expected_channel,
+        );
+
+        assert_eq!(fri_layers.len(), expected_layers.len());
+        assert_eq!(
+            channel.get_committed_roots(),
+            expected_channel.get_committed_roots()
+        );
+        assert_eq!(last_poly.coefficients, expected_last_poly.coefficients);
+
+        let not_divisible = &numerator + &Polynome::new_poly(&[FieldElement::ONE]);
+        assert!(
+            FriCodeLayer::<FieldElement, FieldElement>::fri_commit_quotient(
+                &not_divisible,
+                &denominator,
+                domain_size,
+                &config,
+                &mut Channel::new(),
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fri_commit_batch() {
+        let polys = [3, 7, 12]
+            .iter()
+            .map(|degree| {
+                Polynome::new_poly(
+                    &(0..=*degree)
+                        .map(|i| FieldElement::from(i * i + degree))
+                        .collect::<Vec<FieldElement>>(),
+                )
+            })
+            .collect::<Vec<Polynome<FieldElement>>>();
+        let config = FriConfig::default();
+        let domain_size = config.domain_size(12);
+
+        let channel = &mut Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) =
+            FriCodeLayer::<_, _, Sha256>::fri_commit_batch(&polys, domain_size, &config, channel);
+
+        // One leaf per domain point with the values of the 3 polynomials
+        let batch_layer = &fri_layers[0];
+        assert_eq!(batch_layer.width, 3);
+        assert_eq!(batch_layer.evaluation.len(), 3 * domain_size);
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(domain_size);
+        assert_eq!(
+            batch_layer.row(5).to_vec(),
+            polys
+                .iter()
+                .map(|p| p.evaluate(&domain[5]))
+                .collect::<Vec<FieldElement>>()
+        );
+        assert_eq!(
+            batch_layer.get_merkle_root(),
+            channel.get_committed_roots()[0]
+        );
+
+        // Degree bounds 16, 8, 4, 2 committed, the combination folds to a constant
+        assert_eq!(fri_layers.len(), 4);
+        assert_eq!(last_poly.coefficients.len(), 1);
+    }
+
+    #[test]
+    fn test_batch_combination() {
+        let polys = [2, 5]
+            .iter()
+            .map(|degree| {
+                Polynome::new_poly(
+                    &(1..=degree + 1)
+                        .map(FieldElement::from)
+                        .collect::<Vec<FieldElement>>(),
+                )
+            })
+            .collect::<Vec<Polynome<FieldElement>>>();
+        let degrees = [2, 5];
+        let alpha = FieldElement::from(3u64);
+        let x = FieldElement::from(10u64);
+
+        // f_0 + 3 x^5 f_0 + 9 f_1 + 27 f_1 with a degree bound of 8
+        let (f0, f1) = (polys[0].evaluate(&x), polys[1].evaluate(&x));
+        let expected = f0
+            + FieldElement::from(3u64) * x.pow([5u64]) * f0
+            + FieldElement::from(9u64) * f1
+            + FieldElement::from(27u64) * x.pow([2u64]) * f1;
+        assert_eq!(
+            batch_combination(&[f0, f1], &x, &alpha, &degrees, 8),
+            expected
+        );
+    }
 }
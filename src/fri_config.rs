@@ -0,0 +1,339 @@
+// Subject: FRI parameters and their security estimation
+//
+// The prover and the verifier agree on a `FriConfig` : blowup factor (the inverse of
+// the code rate rho), number of queries, folding factor, final polynomial degree bound
+// and proof of work (grinding) bits asked to the prover before the queries. In the DEEP
+// mode, FRI runs on the quotient (f(x) - f(z)) / (x - z) at an out of domain point z.
+// In the zero-knowledge mode f is blinded by random coefficients above its degree, one
+// per point opened in the first layer, the leaves are salted and the folding starts from
+// the blinded f plus a random masking polynomial : every opened value is random.
+//
+// Two estimations of the bits of security are given, for the field the challenges are
+// sampled from (the extension field when one is used) :
+// - conjectured : each query rejects a far codeword with probability 1 - rho, the
+//   commitment phase adds an error of about n / |F| (ethSTARK conjecture)
+// - proven : list decoding up to the Johnson bound, each query rejects with probability
+//   1 - (1 + 1/2m) sqrt(rho) and each folding round adds the proximity gaps error
+//   (k - 1) (m + 1/2)^7 n^2 / (3 rho^(3/2) |F|) of [BCIKS20]
+
+use crate::merkle_cap::MAX_MERKLE_CAP_HEIGHT;
+
+// Folding factors supported by the coset openings
+pub const SUPPORTED_FOLDING_FACTORS: [usize; 4] = [2, 4, 8, 16];
+
+// Grinding is a proof of work on the transcript, beyond 32 bits the prover would not end
+pub const MAX_GRINDING_BITS: u32 = 32;
+
+// Multiplicity parameter m of the Johnson bound, m >= 3
+const JOHNSON_MULTIPLICITY: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriConfig {
+    // Evaluation domain size over the degree bound of the polynomial, a power of two
+    pub blowup_factor: usize,
+    pub number_of_queries: usize,
+    // Each folding round divides the degree by the folding factor
+    pub folding_factor: usize,
+    // The final polynomial has a degree lower than this bound
+    pub final_degree_bound: usize,
+    // Leading zero bits of the proof of work required before the queries
+    pub grinding_bits: u32,
+    // DEEP-FRI : the folding starts from the quotient by x - z instead of f
+    pub deep: bool,
+    // Each Merkle leaf holds a whole folding coset, a query opens one leaf per layer
+    pub coset_leaves: bool,
+    // The layers are committed by the 2^k nodes of their Merkle cap of height k
+    // instead of the root, the auth paths are k hashes shorter
+    pub merkle_cap_height: usize,
+    // Zero-knowledge : salted leaves and a masking polynomial committed with f
+    pub zero_knowledge: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FriConfigError {
+    // The blowup factor should be a power of two, at least 2
+    InvalidBlowupFactor(usize),
+    NoQueries,
+    UnsupportedFoldingFactor(usize),
+    // The final degree bound should be at least 1 (constant final polynomial)
+    InvalidFinalDegreeBound(usize),
+    TooManyGrindingBits(u32),
+    TooLargeMerkleCap(usize),
+}
+
+// Blowup 8 and 20 queries, as in the main example
+impl Default for FriConfig {
+    fn default() -> Self {
+        Self {
+            blowup_factor: 8,
+            number_of_queries: 20,
+            folding_factor: 2,
+            final_degree_bound: 1,
+            grinding_bits: 0,
+            deep: false,
+            coset_leaves: false,
+            merkle_cap_height: 0,
+            zero_knowledge: false,
+        }
+    }
+}
+
+impl FriConfig {
+    // Configuration without DEEP, coset leaves, Merkle caps nor zero-knowledge, set `deep`,
+    // `coset_leaves`, `merkle_cap_height` or `zero_knowledge` to enable them
+    pub fn new(
+        blowup_factor: usize,
+        number_of_queries: usize,
+        folding_factor: usize,
+        final_degree_bound: usize,
+        grinding_bits: u32,
+    ) -> Result<Self, FriConfigError> {
+        let config = Self {
+            blowup_factor,
+            number_of_queries,
+            folding_factor,
+            final_degree_bound,
+            grinding_bits,
+            deep: false,
+            coset_leaves: false,
+            merkle_cap_height: 0,
+            zero_knowledge: false,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), FriConfigError> {
+        if self.blowup_factor < 2 || !self.blowup_factor.is_power_of_two() {
+            return Err(FriConfigError::InvalidBlowupFactor(self.blowup_factor));
+        }
+        if self.number_of_queries == 0 {
+            return Err(FriConfigError::NoQueries);
+        }
+        if !SUPPORTED_FOLDING_FACTORS.contains(&self.folding_factor) {
+            return Err(FriConfigError::UnsupportedFoldingFactor(
+                self.folding_factor,
+            ));
+        }
+        if self.final_degree_bound == 0 {
+            return Err(FriConfigError::InvalidFinalDegreeBound(
+                self.final_degree_bound,
+            ));
+        }
+        if self.grinding_bits > MAX_GRINDING_BITS {
+            return Err(FriConfigError::TooManyGrindingBits(self.grinding_bits));
+        }
+        if self.merkle_cap_height > MAX_MERKLE_CAP_HEIGHT {
+            return Err(FriConfigError::TooLargeMerkleCap(self.merkle_cap_height));
+        }
+        Ok(())
+    }
+
+    // Number of domain points per Merkle leaf of the committed layers
+    pub fn leaf_coset_size(&self) -> usize {
+        if self.coset_leaves {
+            self.folding_factor
+        } else {
+            1
+        }
+    }
+
+    // Code rate rho
+    pub fn rate(&self) -> f64 {
+        1.0 / self.blowup_factor as f64
+    }
+
+    // Points of f opened by the queries and the DEEP evaluation, in the zero-knowledge
+    // mode the prover blinds f with as many random coefficients above its degree
+    pub fn zk_opened_points(&self) -> usize {
+        if self.zero_knowledge {
+            self.number_of_queries * self.folding_factor + self.deep as usize
+        } else {
+            0
+        }
+    }
+
+    // Evaluation domain size of a polynomial of the given degree :
+    // the degree bound rounded to a power of two, times the blowup factor
+    // The zero-knowledge mode leaves room in the degree bound to blind the polynomial
+    pub fn domain_size(&self, degree: usize) -> usize {
+        (degree + 1 + self.zk_opened_points()).next_power_of_two() * self.blowup_factor
+    }
+
+    // Number of folding rounds from the domain size down to the final degree bound
+    pub fn number_of_rounds(&self, domain_size: usize) -> usize {
+        let mut degree_bound = domain_size / self.blowup_factor;
+        let mut rounds = 0;
+        while degree_bound > self.final_degree_bound {
+            degree_bound = degree_bound.div_ceil(self.folding_factor);
+            rounds += 1;
+        }
+        rounds
+    }
+
+    // Bits of security under the ethSTARK conjecture, zero if none
+    pub fn conjectured_security_bits(&self, field_size_bits: u32, domain_size: usize) -> f64 {
+        let query_bits = self.number_of_queries as f64 * (self.blowup_factor as f64).log2()
+            + self.grinding_bits as f64;
+        let commit_bits = field_size_bits as f64 - (domain_size as f64).log2();
+
+        query_bits.min(commit_bits).max(0.0)
+    }
+
+    // Bits of security proven up to the Johnson bound, zero if none
+    pub fn proven_security_bits(&self, field_size_bits: u32, domain_size: usize) -> f64 {
+        let m = JOHNSON_MULTIPLICITY;
+        let rate = self.rate();
+
+        let query_error = (1.0 + 1.0 / (2.0 * m)) * rate.sqrt();
+        let query_bits =
+            -(self.number_of_queries as f64) * query_error.log2() + self.grinding_bits as f64;
+
+        // log2 of the proximity gaps error summed over the folding rounds, times |F|
+        let n = domain_size as f64;
+        let rounds = self.number_of_rounds(domain_size).max(1) as f64;
+        let commit_error_log = (rounds * (self.folding_factor - 1) as f64).log2()
+            + 7.0 * (m + 0.5).log2()
+            + 2.0 * n.log2()
+            - (3.0 * rate.powf(1.5)).log2();
+        let commit_bits = field_size_bits as f64 - commit_error_log;
+
+        query_bits.min(commit_bits).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field::FriField;
+    use crate::field_extension::{CubicExtension, QuadraticExtension};
+    use crate::field_provider_goldilocks::Goldilocks;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(FriConfig::default().validate(), Ok(()));
+        assert_eq!(
+            FriConfig::new(8, 20, 4, 1, 0),
+            Ok(FriConfig {
+                folding_factor: 4,
+                ..FriConfig::default()
+            })
+        );
+
+        assert_eq!(
+            FriConfig::new(6, 20, 2, 1, 0),
+            Err(FriConfigError::InvalidBlowupFactor(6))
+        );
+        assert_eq!(
+            FriConfig::new(1, 20, 2, 1, 0),
+            Err(FriConfigError::InvalidBlowupFactor(1))
+        );
+        assert_eq!(
+            FriConfig::new(8, 0, 2, 1, 0),
+            Err(FriConfigError::NoQueries)
+        );
+        assert_eq!(
+            FriConfig::new(8, 20, 32, 1, 0),
+            Err(FriConfigError::UnsupportedFoldingFactor(32))
+        );
+        assert_eq!(
+            FriConfig::new(8, 20, 2, 0, 0),
+            Err(FriConfigError::InvalidFinalDegreeBound(0))
+        );
+        assert_eq!(
+            FriConfig::new(8, 20, 2, 1, 40),
+            Err(FriConfigError::TooManyGrindingBits(40))
+        );
+        assert_eq!(
+            FriConfig {
+                merkle_cap_height: 20,
+                ..FriConfig::default()
+            }
+            .validate(),
+            Err(FriConfigError::TooLargeMerkleCap(20))
+        );
+    }
+
+    #[test]
+    fn test_domain_size_and_rounds() {
+        let config = FriConfig::default();
+
+        // Degree 6 of the main example
+        assert_eq!(config.domain_size(6), 64);
+        assert_eq!(config.domain_size(7), 64);
+        assert_eq!(config.domain_size(8), 128);
+
+        // 20 queries opening 2 points each
+        let zk_config = FriConfig {
+            zero_knowledge: true,
+            ..config
+        };
+        assert_eq!(zk_config.zk_opened_points(), 40);
+        assert_eq!(zk_config.domain_size(6), 512);
+        // The DEEP evaluation opens one more point
+        let deep_zk_config = FriConfig {
+            deep: true,
+            ..zk_config
+        };
+        assert_eq!(deep_zk_config.zk_opened_points(), 41);
+
+        // Degree bounds 8, 4, 2, 1
+        assert_eq!(config.number_of_rounds(64), 3);
+        let config = FriConfig {
+            folding_factor: 16,
+            final_degree_bound: 4,
+            ..config
+        };
+        // Degree bounds 1024, 64, 4
+        assert_eq!(config.number_of_rounds(8192), 2);
+    }
+
+    #[test]
+    fn test_conjectured_security() {
+        let config = FriConfig::default();
+
+        // 20 queries of 3 bits
+        assert_eq!(config.conjectured_security_bits(255, 64), 60.0);
+
+        // Limited by the 64 bits field, but not by its quadratic extension
+        let domain_size = 1 << 20;
+        assert_eq!(
+            config.conjectured_security_bits(Goldilocks::field_size_bits(), domain_size),
+            44.0
+        );
+        assert_eq!(
+            config.conjectured_security_bits(
+                QuadraticExtension::<Goldilocks>::field_size_bits(),
+                domain_size
+            ),
+            60.0
+        );
+
+        let grinding = FriConfig {
+            grinding_bits: 20,
+            ..config
+        };
+        assert_eq!(grinding.conjectured_security_bits(255, 64), 80.0);
+    }
+
+    #[test]
+    fn test_proven_security() {
+        let config = FriConfig {
+            number_of_queries: 80,
+            ..FriConfig::default()
+        };
+        let domain_size = 1 << 20;
+        let field_size_bits = CubicExtension::<Goldilocks>::field_size_bits();
+
+        let proven = config.proven_security_bits(field_size_bits, domain_size);
+        let conjectured = config.conjectured_security_bits(field_size_bits, domain_size);
+
+        // Each query is worth log2(8) / 2 bits at best under the Johnson bound
+        assert!(proven < conjectured);
+        assert!(proven > 80.0 && proven < 120.0);
+
+        // The 64 bits base field is far too small for the proven bound at this size
+        assert!(config.proven_security_bits(Goldilocks::field_size_bits(), domain_size) < 8.0);
+    }
+}
@@ -0,0 +1,611 @@
+// Subject: Self-contained FRI proof and its binary encoding
+//
+// Encoding, all integers little endian :
+//   magic "FRIP" | version u16 | hash function identifier u8 (Merkle trees and transcript)
+//   number of layers u32 | for each layer : Merkle cap, a single root without a cap
+//   number of final coefficients u32 | coefficients (canonical field encoding each)
+//   proof of work nonce u64
+//   DEEP flag u8 (0 or 1) | DEEP evaluation f(z) if the flag is 1
+//   zero-knowledge flag u8 (0 or 1)
+//   number of queries u32 | query indices u64
+//   for each query and each layer :
+//     number of coset evaluations u32 | coset evaluations
+//     (a batch first layer holds the values of every polynomial at each coset point,
+//     followed by the mask in the zero-knowledge mode)
+//     salts of the opened leaves if the zero-knowledge flag is 1
+//   for each layer : auth path of all the queried leaves
+//   where a cap, salts or an auth path is its number of hashes u32 followed by the
+//   hashes (32 bytes each)
+// The final coefficients and the evaluations are elements of the extension E, but for
+// the first layer evaluations which are encoded as elements of the base field F.
+
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::channel::Channel;
+use crate::field::{ExtensionField, FriField};
+use crate::fri_code_layer::FriDecommitment;
+use crate::hasher::{MerkleHasher, Sha256};
+use crate::merkle_cap::decode_cap;
+use crate::polynome::Polynome;
+
+pub const FRI_PROOF_MAGIC: &[u8; 4] = b"FRIP";
+// Bumped on every change of the encoding, a proof of another version is rejected
+pub const FRI_PROOF_VERSION: u16 = 9;
+
+#[derive(Debug)]
+pub enum FriProofError {
+    InvalidMagic,
+    UnsupportedVersion(u16),
+    UnexpectedEnd,
+    InvalidFieldElement,
+    InvalidDeepFlag(u8),
+    InvalidZeroKnowledgeFlag(u8),
+    // The proof was made with the hash function of this identifier
+    HasherMismatch(u8),
+    TrailingBytes,
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for FriProofError {
+    fn from(error: std::io::Error) -> Self {
+        FriProofError::Io(error)
+    }
+}
+
+// Merkle trees hashed with H
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriProof<F: FriField, E: ExtensionField<F> = F, H: MerkleHasher = Sha256> {
+    // Merkle caps of the layers, in commitment order, a single root without a cap
+    pub layers_caps: Vec<Vec<[u8; 32]>>,
+    pub last_poly_coefficients: Vec<E>,
+    // Zero when the configuration asks for no grinding
+    pub pow_nonce: u64,
+    // f(z) in the DEEP mode
+    pub deep_evaluation: Option<E>,
+    pub queries: Vec<usize>,
+    // Openings of every query and one auth path per layer, with the salts of the opened
+    // leaves in the zero-knowledge mode
+    pub decommitment: FriDecommitment<E, H>,
+    base_field: PhantomData<F>,
+}
+
+impl<F: FriField, E: ExtensionField<F>, H: MerkleHasher> FriProof<F, E, H> {
+    // Gather the prover outputs once the commitment and decommitment phases are done
+    pub fn new(
+        channel: &Channel<E, H>,
+        last_poly: &Polynome<E>,
+        decommitment: FriDecommitment<E, H>,
+        queries: Vec<usize>,
+    ) -> Self {
+        let layers_caps = channel
+            .get_committed_roots()
+            .into_iter()
+            .map(|cap| {
+                decode_cap(&cap.expect("Committed layers have a Merkle cap"))
+                    .expect("Merkle caps are hex encoded hashes")
+            })
+            .collect();
+
+        Self {
+            layers_caps,
+            last_poly_coefficients: last_poly.coefficients.clone(),
+            pow_nonce: channel.get_pow_nonce().unwrap_or_default(),
+            deep_evaluation: channel.get_deep_evaluation(),
+            queries,
+            decommitment,
+            base_field: PhantomData,
+        }
+    }
+
+    pub fn last_poly(&self) -> Polynome<E> {
+        Polynome::new_poly(&self.last_poly_coefficients)
+    }
+
+    // Salted leaves, made in the zero-knowledge mode
+    pub fn is_zero_knowledge(&self) -> bool {
+        self.decommitment
+            .queries_salts
+            .iter()
+            .flatten()
+            .any(|salts| !salts.is_empty())
+    }
+
+    // Panics if a first layer evaluation is not in the base field
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(FRI_PROOF_MAGIC);
+        bytes.extend_from_slice(&FRI_PROOF_VERSION.to_le_bytes());
+        bytes.push(H::ID);
+
+        write_len(&mut bytes, self.layers_caps.len());
+        self.layers_caps
+            .iter()
+            .for_each(|cap| write_auth_path(&mut bytes, cap));
+
+        write_len(&mut bytes, self.last_poly_coefficients.len());
+        self.last_poly_coefficients
+            .iter()
+            .for_each(|c| write_field_element(&mut bytes, c));
+
+        bytes.extend_from_slice(&self.pow_nonce.to_le_bytes());
+
+        match &self.deep_evaluation {
+            Some(value) => {
+                bytes.push(1);
+                write_field_element(&mut bytes, value);
+            }
+            None => bytes.push(0),
+        }
+
+        let zero_knowledge = self.is_zero_knowledge();
+        bytes.push(zero_knowledge as u8);
+
+        write_len(&mut bytes, self.queries.len());
+        self.queries
+            .iter()
+            .for_each(|q| bytes.extend_from_slice(&(*q as u64).to_le_bytes()));
+
+        for (q, layers_evaluations) in self.decommitment.queries_evaluations.iter().enumerate() {
+            for (layer, evaluations) in layers_evaluations.iter().enumerate() {
+                write_len(&mut bytes, evaluations.len());
+                evaluations
+                    .iter()
+                    .for_each(|value| write_layer_element::<F, E>(&mut bytes, layer, value));
+                if zero_knowledge {
+                    write_auth_path(&mut bytes, &self.decommitment.queries_salts[q][layer]);
+                }
+            }
+        }
+        self.decommitment
+            .layers_auth_paths
+            .iter()
+            .for_each(|auth_path| write_auth_path(&mut bytes, auth_path));
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FriProofError> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(4)? != FRI_PROOF_MAGIC {
+            return Err(FriProofError::InvalidMagic);
+        }
+        let version = u16::from_le_bytes(reader.take_array()?);
+        if version != FRI_PROOF_VERSION {
+            return Err(FriProofError::UnsupportedVersion(version));
+        }
+        let hasher_id = reader.take_array::<1>()?[0];
+        if hasher_id != H::ID {
+            return Err(FriProofError::HasherMismatch(hasher_id));
+        }
+
+        let number_of_layers = reader.read_len()?;
+        let layers_caps = (0..number_of_layers)
+            .map(|_| reader.read_auth_path())
+            .collect::<Result<Vec<Vec<[u8; 32]>>, FriProofError>>()?;
+
+        let number_of_coefficients = reader.read_len()?;
+        let last_poly_coefficients = (0..number_of_coefficients)
+            .map(|_| reader.read_field_element())
+            .collect::<Result<Vec<E>, FriProofError>>()?;
+
+        let pow_nonce = u64::from_le_bytes(reader.take_array()?);
+
+        let deep_evaluation = match reader.take_array::<1>()?[0] {
+            0 => None,
+            1 => Some(reader.read_field_element()?),
+            flag => return Err(FriProofError::InvalidDeepFlag(flag)),
+        };
+
+        let zero_knowledge = match reader.take_array::<1>()?[0] {
+            0 => false,
+            1 => true,
+            flag => return Err(FriProofError::InvalidZeroKnowledgeFlag(flag)),
+        };
+
+        let number_of_queries = reader.read_len()?;
+        let queries = (0..number_of_queries)
+            .map(|_| Ok(u64::from_le_bytes(reader.take_array()?) as usize))
+            .collect::<Result<Vec<usize>, FriProofError>>()?;
+
+        let mut queries_evaluations = Vec::with_capacity(number_of_queries);
+        let mut queries_salts = Vec::with_capacity(number_of_queries);
+        for _ in 0..number_of_queries {
+            let mut layers_evaluations = Vec::with_capacity(number_of_layers);
+            let mut layers_salts = Vec::with_capacity(number_of_layers);
+            for layer in 0..number_of_layers {
+                let number_of_evaluations = reader.read_len()?;
+                let evaluations = (0..number_of_evaluations)
+                    .map(|_| reader.read_layer_element::<F, E>(layer))
+                    .collect::<Result<Vec<E>, FriProofError>>()?;
+                layers_evaluations.push(evaluations);
+                layers_salts.push(if zero_knowledge {
+                    reader.read_auth_path()?
+                } else {
+                    vec![]
+                });
+            }
+            queries_evaluations.push(layers_evaluations);
+            queries_salts.push(layers_salts);
+        }
+
+        // No auth path without a query
+        let number_of_auth_paths = if number_of_queries > 0 {
+            number_of_layers
+        } else {
+            0
+        };
+        let layers_auth_paths = (0..number_of_auth_paths)
+            .map(|_| reader.read_auth_path())
+            .collect::<Result<Vec<Vec<[u8; 32]>>, FriProofError>>()?;
+
+        if reader.position != bytes.len() {
+            return Err(FriProofError::TrailingBytes);
+        }
+
+        Ok(Self {
+            layers_caps,
+            last_poly_coefficients,
+            pow_nonce,
+            deep_evaluation,
+            queries,
+            decommitment: FriDecommitment::new(
+                queries_evaluations,
+                queries_salts,
+                layers_auth_paths,
+            ),
+            base_field: PhantomData,
+        })
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FriProofError> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, FriProofError> {
+        FriProof::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn write_field_element<F: FriField>(bytes: &mut Vec<u8>, value: &F) {
+    bytes.extend_from_slice(&value.to_bytes());
+}
+
+fn write_layer_element<F: FriField, E: ExtensionField<F>>(
+    bytes: &mut Vec<u8>,
+    layer: usize,
+    value: &E,
+) {
+    if layer == 0 {
+        let base = value
+            .as_base()
+            .expect("First layer evaluations are in the base field");
+        write_field_element(bytes, &base);
+    } else {
+        write_field_element(bytes, value);
+    }
+}
+
+fn write_auth_path(bytes: &mut Vec<u8>, auth_path: &[[u8; 32]]) {
+    write_len(bytes, auth_path.len());
+    auth_path
+        .iter()
+        .for_each(|hash| bytes.extend_from_slice(hash));
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], FriProofError> {
+        let end = self
+            .position
+            .checked_add(n)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(FriProofError::UnexpectedEnd)?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], FriProofError> {
+        Ok(self
+            .take(N)?
+            .try_into()
+            .expect("Slice of the requested length"))
+    }
+
+    fn read_len(&mut self) -> Result<usize, FriProofError> {
+        Ok(u32::from_le_bytes(self.take_array()?) as usize)
+    }
+
+    fn read_field_element<F: FriField>(&mut self) -> Result<F, FriProofError> {
+        F::from_bytes(self.take(F::encoded_len())?).ok_or(FriProofError::InvalidFieldElement)
+    }
+
+    fn read_layer_element<F: FriField, E: ExtensionField<F>>(
+        &mut self,
+        layer: usize,
+    ) -> Result<E, FriProofError> {
+        if layer == 0 {
+            Ok(E::from_base(self.read_field_element::<F>()?))
+        } else {
+            self.read_field_element()
+        }
+    }
+
+    fn read_auth_path(&mut self) -> Result<Vec<[u8; 32]>, FriProofError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.take_array()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_extension::CubicExtension;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+    use crate::fri_code_layer::FriCodeLayer;
+    use crate::fri_config::FriConfig;
+    use crate::hasher::Keccak256;
+
+    fn sample_proof() -> FriProof<FieldElement> {
+        let coefficients = vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+            FieldElement::from(4u64),
+            FieldElement::from(5u64),
+        ];
+        let domain_size = 64;
+        let config = FriConfig {
+            number_of_queries: 5,
+            grinding_bits: 4,
+            ..FriConfig::default()
+        };
+        let poly = Polynome::new_poly(&coefficients);
+        let mut channel = Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) =
+            FriCodeLayer::fri_commit_phase(poly, domain_size, &config, &mut channel);
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+
+        FriProof::new(&channel, &last_poly, decom, queries)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let proof = sample_proof();
+        let bytes = proof.to_bytes();
+
+        assert_eq!(&bytes[..4], FRI_PROOF_MAGIC);
+        assert_eq!(FriProof::<FieldElement>::from_bytes(&bytes).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_round_trip_in_extension() {
+        type E = CubicExtension<Goldilocks>;
+        let coefficients = (1..=5).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 64;
+        let config = FriConfig {
+            number_of_queries: 5,
+            folding_factor: 4,
+            ..FriConfig::default()
+        };
+        let mut channel = Channel::<E>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(
+            Polynome::new_poly(&coefficients),
+            domain_size,
+            &config,
+            &mut channel,
+        );
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+        let proof = FriProof::<Goldilocks, E>::new(&channel, &last_poly, decom, queries);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            FriProof::<Goldilocks, E>::from_bytes(&bytes).unwrap(),
+            proof
+        );
+
+        // Degrees 4, 1 on domains of size 64, 16 : cosets of 4 evaluations
+        // The first layer evaluations are written as 8 bytes base field elements
+        assert_eq!(proof.layers_caps.len(), 2);
+        let base_bytes = 5 * (4 + 4 * Goldilocks::encoded_len());
+        let extension_bytes = 5 * (4 + 4 * E::encoded_len());
+        let auth_paths_bytes = proof
+            .decommitment
+            .layers_auth_paths
+            .iter()
+            .map(|path| 4 + 32 * path.len())
+            .sum::<usize>();
+        // Each cap is a single root
+        let header_bytes =
+            4 + 2 + 1 + 4 + (4 + 32) * proof.layers_caps.len() + 4 + 8 + 1 + 1 + 4 + 8 * 5;
+        assert_eq!(
+            bytes.len(),
+            header_bytes
+                + proof.last_poly_coefficients.len() * E::encoded_len()
+                + base_bytes
+                + extension_bytes
+                + auth_paths_bytes
+        );
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let proof = sample_proof();
+        let path = std::env::temp_dir().join("fri_basic_rustling_test_proof.bin");
+
+        proof.write_to_file(&path).unwrap();
+        let read_proof = FriProof::<FieldElement>::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_proof, proof);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = sample_proof().to_bytes();
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&wrong_magic),
+            Err(FriProofError::InvalidMagic)
+        ));
+
+        // A proof in a former layout is not misparsed
+        for version in [FRI_PROOF_VERSION - 1, FRI_PROOF_VERSION + 1] {
+            let mut wrong_version = bytes.clone();
+            wrong_version[4..6].copy_from_slice(&version.to_le_bytes());
+            assert!(matches!(
+                FriProof::<FieldElement>::from_bytes(&wrong_version),
+                Err(FriProofError::UnsupportedVersion(v)) if v == version
+            ));
+        }
+
+        // A proof made with another hash function
+        let mut wrong_hasher = bytes.clone();
+        wrong_hasher[6] = Keccak256::ID;
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&wrong_hasher),
+            Err(FriProofError::HasherMismatch(2))
+        ));
+
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FriProofError::UnexpectedEnd)
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&trailing),
+            Err(FriProofError::TrailingBytes)
+        ));
+    }
+
+    #[test]
+    fn test_decode_non_canonical_field_element() {
+        let proof = sample_proof();
+        let mut bytes = proof.to_bytes();
+
+        // First final coefficient, right after the header and the roots
+        let offset = 4 + 2 + 1 + 4 + (4 + 32) * proof.layers_caps.len() + 4;
+        bytes[offset..offset + 32].copy_from_slice(&[0xff; 32]);
+
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&bytes),
+            Err(FriProofError::InvalidFieldElement)
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_with_deep() {
+        let coefficients = (1..=5)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let domain_size = 64;
+        let config = FriConfig {
+            number_of_queries: 5,
+            deep: true,
+            ..FriConfig::default()
+        };
+        let mut channel = Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(
+            Polynome::new_poly(&coefficients),
+            domain_size,
+            &config,
+            &mut channel,
+        );
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+        let proof = FriProof::new(&channel, &last_poly, decom, queries);
+        assert!(proof.deep_evaluation.is_some());
+
+        let bytes = proof.to_bytes();
+        assert_eq!(FriProof::<FieldElement>::from_bytes(&bytes).unwrap(), proof);
+        let without_deep = FriProof {
+            deep_evaluation: None,
+            ..proof.clone()
+        };
+        assert_eq!(
+            bytes.len(),
+            without_deep.to_bytes().len() + FieldElement::encoded_len()
+        );
+
+        // DEEP flag, right after the proof of work nonce
+        let offset = 4
+            + 2
+            + 1
+            + 4
+            + (4 + 32) * proof.layers_caps.len()
+            + 4
+            + FieldElement::encoded_len() * proof.last_poly_coefficients.len()
+            + 8;
+        let mut wrong_flag = bytes.clone();
+        wrong_flag[offset] = 2;
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&wrong_flag),
+            Err(FriProofError::InvalidDeepFlag(2))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_zero_knowledge() {
+        let coefficients = (1..=5)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let config = FriConfig {
+            number_of_queries: 5,
+            zero_knowledge: true,
+            ..FriConfig::default()
+        };
+        let domain_size = config.domain_size(4);
+        let mut channel = Channel::<FieldElement>::new();
+        let (last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(
+            Polynome::new_poly(&coefficients),
+            domain_size,
+            &config,
+            &mut channel,
+        );
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+        let proof = FriProof::new(&channel, &last_poly, decom, queries);
+        assert!(proof.is_zero_knowledge());
+        assert!(!sample_proof().is_zero_knowledge());
+
+        let bytes = proof.to_bytes();
+        assert_eq!(FriProof::<FieldElement>::from_bytes(&bytes).unwrap(), proof);
+
+        // Zero-knowledge flag, right after the DEEP flag
+        let offset = 4
+            + 2
+            + 1
+            + 4
+            + (4 + 32) * proof.layers_caps.len()
+            + 4
+            + FieldElement::encoded_len() * proof.last_poly_coefficients.len()
+            + 8
+            + 1;
+        assert_eq!(bytes[offset], 1);
+        let mut wrong_flag = bytes.clone();
+        wrong_flag[offset] = 2;
+        assert!(matches!(
+            FriProof::<FieldElement>::from_bytes(&wrong_flag),
+            Err(FriProofError::InvalidZeroKnowledgeFlag(2))
+        ));
+    }
+}
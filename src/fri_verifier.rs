@@ -0,0 +1,1840 @@
+// Subject: FRI verifier implementation
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use crate::channel::Channel;
+use crate::domain::Domain;
+use crate::field::{ExtensionField, TwoAdicField};
+use crate::fri_code_layer::{
+    batch_combination, build_next_domain, coset_indices, deep_quotient, degree_correction,
+    generate_enlarged_evaluation_domain, hash_layer_row, position_in_coset, query_leaves,
+    FriDecommitment,
+};
+use crate::fri_config::FriConfig;
+use crate::fri_proof::FriProof;
+use crate::hasher::{MerkleHasher, Sha256};
+use crate::merkle_cap::{decode_cap, verify_cap_proof};
+use crate::polynome::Polynome;
+
+// Why a query has been rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FriRejectionReason {
+    // No Merkle root or cap has been committed on the channel for the layer
+    MissingCommitment,
+    // The decommitment does not hold one opening per committed layer
+    MalformedDecommitment,
+    // The number of committed layers does not match the configuration
+    WrongNumberOfLayers,
+    // The final polynomial sent in the clear exceeds the final degree bound
+    FinalDegreeTooLarge,
+    // The proof of work nonce does not have the required leading zero bits
+    InvalidProofOfWork,
+    // The DEEP mode is on but the prover did not send f(z)
+    MissingDeepEvaluation,
+    // The first layer root is not the commitment being opened
+    CommitmentMismatch,
+    // The query index is not the one derived from the transcript
+    QueryIndexMismatch,
+    // The committed Merkle cap of the layer does not have the size set by the configuration
+    WrongCapSize,
+    // The auth path of the queried coset does not lead to the layer root or cap
+    InvalidAuthPath,
+    // The folding of the layer with beta does not give the next layer evaluation
+    FoldingMismatch,
+    // The last layer does not fold into the final polynomial
+    FinalLayerMismatch,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FriVerificationResult {
+    Accept,
+    Reject {
+        query: usize,
+        layer: usize,
+        reason: FriRejectionReason,
+    },
+}
+
+impl FriVerificationResult {
+    pub fn is_accepted(&self) -> bool {
+        *self == FriVerificationResult::Accept
+    }
+}
+
+// Domains in the base field F, challenges and folded layers in the extension E,
+// Merkle trees hashed with H
+pub struct FriVerifier<F: TwoAdicField, E: ExtensionField<F> = F, H: MerkleHasher = Sha256> {
+    // Merkle caps of each layer, in commitment order, a single root without a cap
+    layers_caps: Vec<Option<Vec<[u8; 32]>>>,
+    // Beta challenges, betas[i] folds the layer i into the layer i + 1,
+    // the last one folds the last committed layer into the final polynomial
+    betas: Vec<E>,
+    // Domain of each folding round input, followed by the domain of the final polynomial
+    layers_domains: Vec<Domain<F>>,
+    config: FriConfig,
+    // Proof of work nonce sent by the prover before the queries
+    pow_nonce: Option<u64>,
+    // DEEP mode : out of domain point z from the transcript and f(z) sent by the prover
+    // Opening : point z and value claimed for f(z)
+    quotient_point: Option<E>,
+    quotient_value: Option<E>,
+    // Challenge of the degree correction of the quotient and its coefficients bound
+    degree_correction: Option<(E, usize)>,
+    // Batch : combination challenge and degrees of the polynomials of the first layer
+    batch: Option<(E, Vec<usize>)>,
+    // Zero-knowledge : coefficient of the mask committed at the end of the first layer rows
+    mask_challenge: Option<E>,
+    last_poly: Polynome<E>,
+    // Transcript replayed up to the end of the commitment phase, used to derive the queries
+    transcript_channel: Channel<E, H>,
+    hasher: PhantomData<H>,
+}
+
+// What the folding starts from, f or its quotient by x - z
+enum FirstLayerQuotient<E> {
+    None,
+    // DEEP mode, with f(z) if the prover sent it
+    Deep(Option<E>),
+    // Opening of a polynomial of degree at most max_degree
+    Opening {
+        point: E,
+        value: E,
+        max_degree: usize,
+    },
+    // Batch of polynomials of the given degrees
+    Batch(Vec<usize>),
+}
+
+impl<F: TwoAdicField, E: ExtensionField<F>, H: MerkleHasher> FriVerifier<F, E, H> {
+    // The verifier reads the committed roots from the channel and replays the transcript
+    // on its own to derive the beta challenges, it then rebuilds the layers domains
+    // from the initial domain size
+    pub fn new(
+        domain_size: usize,
+        config: &FriConfig,
+        channel: &Channel<E, H>,
+        last_poly: &Polynome<E>,
+    ) -> Self {
+        Self::new_from_roots(
+            domain_size,
+            config,
+            channel.get_label(),
+            channel.get_committed_roots(),
+            channel.get_pow_nonce(),
+            channel.get_deep_evaluation(),
+            last_poly,
+        )
+    }
+
+    // Verifier of a proof received from another process, the domain size, the
+    // configuration and the label are the public parameters agreed with the prover
+    pub fn from_proof(
+        domain_size: usize,
+        config: &FriConfig,
+        label: &[u8],
+        proof: &FriProof<F, E, H>,
+    ) -> Self {
+        Self::new_from_roots(
+            domain_size,
+            config,
+            label,
+            Self::proof_roots(proof),
+            Some(proof.pow_nonce),
+            proof.deep_evaluation,
+            &proof.last_poly(),
+        )
+    }
+
+    // Roots of the committed layers as the transcript encodes them, one per layer cap
+    fn proof_roots(proof: &FriProof<F, E, H>) -> Vec<Option<String>> {
+        proof
+            .layers_caps
+            .iter()
+            .map(|cap| Some(hex::encode(cap.concat())))
+            .collect()
+    }
+
+    pub fn new_from_roots(
+        domain_size: usize,
+        config: &FriConfig,
+        label: &[u8],
+        roots: Vec<Option<String>>,
+        pow_nonce: Option<u64>,
+        deep_evaluation: Option<E>,
+        last_poly: &Polynome<E>,
+    ) -> Self {
+        let quotient = if config.deep {
+            FirstLayerQuotient::Deep(deep_evaluation)
+        } else {
+            FirstLayerQuotient::None
+        };
+        Self::replay(
+            domain_size,
+            config,
+            label,
+            roots,
+            pow_nonce,
+            quotient,
+            last_poly,
+        )
+    }
+
+    // Verifier of the opening of a commitment at the point to the value : the proof is
+    // a FRI proof of the quotient (f(x) - value) / (x - point) where f is the first layer,
+    // of degree at most max_degree
+    pub fn for_opening(
+        domain_size: usize,
+        config: &FriConfig,
+        label: &[u8],
+        proof: &FriProof<F, E, H>,
+        point: &E,
+        value: &E,
+        max_degree: usize,
+    ) -> Self {
+        assert!(
+            max_degree < domain_size / config.blowup_factor,
+            "Polynomial degree exceeds the domain degree bound"
+        );
+        Self::replay(
+            domain_size,
+            config,
+            label,
+            Self::proof_roots(proof),
+            Some(proof.pow_nonce),
+            FirstLayerQuotient::Opening {
+                point: *point,
+                value: *value,
+                max_degree,
+            },
+            &proof.last_poly(),
+        )
+    }
+
+    // Verifier of a batch proof : the first layer commits the polynomials of the given
+    // degrees together, FRI runs on their degree-corrected random combination
+    // In the zero-knowledge mode the degrees are raised by the blinding coefficients
+    pub fn for_batch(
+        domain_size: usize,
+        config: &FriConfig,
+        label: &[u8],
+        proof: &FriProof<F, E, H>,
+        degrees: &[usize],
+    ) -> Self {
+        let degrees = degrees
+            .iter()
+            .map(|d| d + config.zk_opened_points())
+            .collect::<Vec<usize>>();
+        assert!(
+            degrees
+                .iter()
+                .all(|d| *d < domain_size / config.blowup_factor),
+            "Polynomial degree exceeds the domain degree bound"
+        );
+        Self::replay(
+            domain_size,
+            config,
+            label,
+            Self::proof_roots(proof),
+            Some(proof.pow_nonce),
+            FirstLayerQuotient::Batch(degrees),
+            &proof.last_poly(),
+        )
+    }
+
+    fn replay(
+        domain_size: usize,
+        config: &FriConfig,
+        label: &[u8],
+        roots: Vec<Option<String>>,
+        pow_nonce: Option<u64>,
+        quotient: FirstLayerQuotient<E>,
+        last_poly: &Polynome<E>,
+    ) -> Self {
+        config.validate().expect("Invalid FRI configuration");
+        let number_of_rounds = config.number_of_rounds(domain_size);
+
+        // Same transcript as the prover : every root but the first one is preceded by
+        // its challenge, the final polynomial comes after the last challenge
+        // In the DEEP mode or for an opening, z and f(z) come right after the first root,
+        // followed by the degree correction challenge of the quotient, then by the masking
+        // challenge in the zero-knowledge mode
+        let mut transcript_channel = Channel::new_with_label(label);
+        let mut betas = Vec::with_capacity(number_of_rounds);
+        let mut beta = E::ZERO;
+        let (mut quotient_point, mut quotient_value) = (None, None);
+        // The quotient has one coefficient less than f
+        let mut quotient_bound = domain_size / config.blowup_factor - 1;
+        let mut batch = None;
+        let mut degree_correction = None;
+        let mut mask_challenge = None;
+        for (i, root) in roots.iter().enumerate() {
+            transcript_channel.add_committed_data(beta, root.clone());
+            if i == 0 {
+                match quotient {
+                    FirstLayerQuotient::None => {}
+                    FirstLayerQuotient::Deep(value) => {
+                        quotient_point = Some(transcript_channel.get_deep_point());
+                        quotient_value = value;
+                        if let Some(value) = value {
+                            transcript_channel.add_deep_evaluation(value);
+                        }
+                    }
+                    FirstLayerQuotient::Opening {
+                        point,
+                        value,
+                        max_degree,
+                    } => {
+                        transcript_channel.add_opening(point, value);
+                        (quotient_point, quotient_value) = (Some(point), Some(value));
+                        quotient_bound = max_degree;
+                    }
+                    FirstLayerQuotient::Batch(ref degrees) => {
+                        batch = Some((transcript_channel.get_batch_challenge(), degrees.clone()));
+                    }
+                }
+                if quotient_point.is_some() {
+                    degree_correction = Some((
+                        transcript_channel.get_degree_correction_challenge(),
+                        quotient_bound,
+                    ));
+                }
+                if config.zero_knowledge {
+                    mask_challenge = Some(transcript_channel.get_mask_challenge());
+                }
+            }
+            if i < number_of_rounds {
+                beta = transcript_channel.get_challenge();
+                betas.push(beta);
+            }
+        }
+        transcript_channel.add_final_polynomial(&last_poly.coefficients);
+
+        let layers_caps = roots
+            .into_iter()
+            .map(|cap| decode_cap(&cap?))
+            .collect::<Vec<Option<Vec<[u8; 32]>>>>();
+
+        let mut layers_domains = vec![generate_enlarged_evaluation_domain(domain_size)];
+        for round in 0..number_of_rounds {
+            let next_domain = build_next_domain(&layers_domains[round], config.folding_factor);
+            layers_domains.push(next_domain);
+        }
+
+        Self {
+            layers_caps,
+            betas,
+            layers_domains,
+            config: *config,
+            pow_nonce,
+            quotient_point,
+            quotient_value,
+            degree_correction,
+            batch,
+            mask_challenge,
+            last_poly: last_poly.clone(),
+            transcript_channel,
+            hasher: PhantomData,
+        }
+    }
+
+    pub fn verify_proof(&self, proof: &FriProof<F, E, H>) -> FriVerificationResult {
+        self.verify(&proof.decommitment, &proof.queries)
+    }
+
+    pub fn verify(
+        &self,
+        decommitment: &FriDecommitment<E, H>,
+        queries: &[usize],
+    ) -> FriVerificationResult {
+        if let Some(layer) = self.layers_caps.iter().position(Option::is_none) {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer,
+                reason: FriRejectionReason::MissingCommitment,
+            };
+        }
+
+        // One committed layer per folding round, the initial layer when there is none
+        let number_of_layers = (self.layers_domains.len() - 1).max(1);
+        if self.layers_caps.len() != number_of_layers {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: self.layers_caps.len().min(number_of_layers),
+                reason: FriRejectionReason::WrongNumberOfLayers,
+            };
+        }
+
+        if self.last_poly.coefficients.len() > self.config.final_degree_bound {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: number_of_layers,
+                reason: FriRejectionReason::FinalDegreeTooLarge,
+            };
+        }
+
+        if self.quotient_point.is_some() && self.quotient_value.is_none() {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::MissingDeepEvaluation,
+            };
+        }
+
+        let domain_size = self.layers_domains[0].size();
+        let mut transcript_channel = self.transcript_channel.clone();
+
+        let grinding_bits = self.config.grinding_bits;
+        if grinding_bits > 0
+            && !self
+                .pow_nonce
+                .is_some_and(|nonce| transcript_channel.check_proof_of_work(grinding_bits, nonce))
+        {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::InvalidProofOfWork,
+            };
+        }
+
+        for (query, index) in queries.iter().enumerate() {
+            if *index != transcript_channel.get_index() % domain_size {
+                return FriVerificationResult::Reject {
+                    query,
+                    layer: 0,
+                    reason: FriRejectionReason::QueryIndexMismatch,
+                };
+            }
+        }
+
+        let queries_evaluations = &decommitment.queries_evaluations;
+        if queries_evaluations.len() != queries.len()
+            || queries.len() != self.config.number_of_queries
+        {
+            return FriVerificationResult::Reject {
+                query: queries_evaluations.len().min(queries.len()),
+                layer: 0,
+                reason: FriRejectionReason::MalformedDecommitment,
+            };
+        }
+        if let Some(query) = queries_evaluations
+            .iter()
+            .position(|evaluations| evaluations.len() != number_of_layers)
+        {
+            return FriVerificationResult::Reject {
+                query,
+                layer: 0,
+                reason: FriRejectionReason::MalformedDecommitment,
+            };
+        }
+        if decommitment.layers_auth_paths.len() != number_of_layers {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: decommitment.layers_auth_paths.len().min(number_of_layers),
+                reason: FriRejectionReason::MalformedDecommitment,
+            };
+        }
+
+        for layer in 0..number_of_layers {
+            if let Err((query, reason)) = self.verify_layer_auth_path(decommitment, queries, layer)
+            {
+                return FriVerificationResult::Reject {
+                    query,
+                    layer,
+                    reason,
+                };
+            }
+        }
+
+        for (query, (layers_evaluations, index)) in
+            queries_evaluations.iter().zip(queries).enumerate()
+        {
+            if let Err((layer, reason)) = self.verify_query(layers_evaluations, *index) {
+                return FriVerificationResult::Reject {
+                    query,
+                    layer,
+                    reason,
+                };
+            }
+        }
+
+        FriVerificationResult::Accept
+    }
+
+    // Number of values per domain point of the layer, the number of polynomials for a
+    // batch first layer, plus the mask for a zero-knowledge first layer
+    fn layer_width(&self, layer: usize) -> usize {
+        let mask = (layer == 0 && self.mask_challenge.is_some()) as usize;
+        match (layer, &self.batch) {
+            (0, Some((_, degrees))) => degrees.len() + mask,
+            _ => 1 + mask,
+        }
+    }
+
+    // Check the multi-proof of the layer against the leaves opened by all the queries,
+    // returns the failing query if any
+    // A failing multi-proof covers every query and is reported on the first one
+    fn verify_layer_auth_path(
+        &self,
+        decommitment: &FriDecommitment<E, H>,
+        queries: &[usize],
+        layer: usize,
+    ) -> Result<(), (usize, FriRejectionReason)> {
+        let cap = self.layers_caps[layer]
+            .as_ref()
+            .ok_or((0, FriRejectionReason::MissingCommitment))?;
+        let dom_size = self.layers_domains[layer].size();
+        let width = self.layer_width(layer);
+
+        // Leaves by index with the first query opening them, two queries opening the same
+        // leaf should agree on it
+        let mut leaves = BTreeMap::new();
+        for (query, (layers_evaluations, index)) in decommitment
+            .queries_evaluations
+            .iter()
+            .zip(queries)
+            .enumerate()
+        {
+            // Same coset as the prover
+            let indices = coset_indices(*index, dom_size, self.config.folding_factor);
+            let evaluations = &layers_evaluations[layer];
+            if evaluations.len() != indices.len() * width {
+                return Err((query, FriRejectionReason::MalformedDecommitment));
+            }
+
+            let leaves_indices = query_leaves(
+                *index,
+                dom_size,
+                self.config.folding_factor,
+                self.config.coset_leaves,
+            );
+            // One salt per opened leaf in the zero-knowledge mode, none otherwise
+            let salts = decommitment
+                .queries_salts
+                .get(query)
+                .and_then(|layers_salts| layers_salts.get(layer))
+                .map_or(&[][..], Vec::as_slice);
+            let salts_count = if self.config.zero_knowledge {
+                leaves_indices.len()
+            } else {
+                0
+            };
+            if salts.len() != salts_count {
+                return Err((query, FriRejectionReason::MalformedDecommitment));
+            }
+
+            // The first layer openings should be base field elements
+            // With coset leaves the coset is a single leaf, indexed by its first point
+            let leaves_hashes = if self.config.coset_leaves {
+                vec![hash_layer_row::<H, F, E>(layer, evaluations, salts.first())]
+            } else {
+                evaluations
+                    .chunks(width)
+                    .enumerate()
+                    .map(|(j, row)| hash_layer_row::<H, F, E>(layer, row, salts.get(j)))
+                    .collect()
+            };
+            for (leaf_index, leaf) in leaves_indices.into_iter().zip(leaves_hashes) {
+                let leaf = leaf.ok_or((query, FriRejectionReason::MalformedDecommitment))?;
+                let (first_leaf, first_query) = *leaves.entry(leaf_index).or_insert((leaf, query));
+                if first_leaf != leaf {
+                    return Err((first_query, FriRejectionReason::InvalidAuthPath));
+                }
+            }
+        }
+
+        let leaves_count = if self.config.coset_leaves {
+            dom_size / self.config.folding_factor.min(dom_size)
+        } else {
+            dom_size
+        };
+        let (leaves_indices, leaves): (Vec<usize>, Vec<[u8; 32]>) = leaves
+            .into_iter()
+            .map(|(index, (leaf, _))| (index, leaf))
+            .unzip();
+        // The cap height is lowered to the height of the layer tree
+        let cap_height = self
+            .config
+            .merkle_cap_height
+            .min(leaves_count.ilog2() as usize);
+        if cap.len() != 1 << cap_height {
+            return Err((0, FriRejectionReason::WrongCapSize));
+        }
+        if !verify_cap_proof::<H>(
+            cap,
+            leaves_count,
+            &leaves_indices,
+            &leaves,
+            &decommitment.layers_auth_paths[layer],
+        ) {
+            return Err((0, FriRejectionReason::InvalidAuthPath));
+        }
+        Ok(())
+    }
+
+    // Check the folding of one query through all the layers, whose openings have been
+    // authenticated, returns the failing layer if any
+    fn verify_query(
+        &self,
+        layers_evaluations: &[Vec<E>],
+        query_index: usize,
+    ) -> Result<(), (usize, FriRejectionReason)> {
+        let number_of_layers = self.layers_caps.len();
+
+        for layer in 0..number_of_layers {
+            let domain = &self.layers_domains[layer];
+            let dom_size = domain.size();
+            let indices = coset_indices(query_index, dom_size, self.config.folding_factor);
+            let mut width = self.layer_width(layer);
+            let mut evaluations = layers_evaluations[layer].clone();
+
+            // In the zero-knowledge mode the mask ends each first layer row
+            let mut masks = vec![];
+            if layer == 0 && self.mask_challenge.is_some() {
+                masks = evaluations
+                    .chunks(width)
+                    .map(|row| row[width - 1])
+                    .collect();
+                evaluations = evaluations
+                    .chunks(width)
+                    .flat_map(|row| row[..width - 1].to_vec())
+                    .collect();
+                width -= 1;
+            }
+
+            // In the DEEP mode or for an opening the first layer opens f, the folding starts
+            // from its quotient, for a batch from the combination of the polynomials
+            let mut evaluations =
+                match (layer, &self.batch, self.quotient_point, self.quotient_value) {
+                    (0, Some((alpha, degrees)), _, _) => {
+                        let degree_bound = dom_size / self.config.blowup_factor;
+                        evaluations
+                            .chunks(width)
+                            .zip(&indices)
+                            .map(|(row, i)| {
+                                batch_combination(row, &domain[*i], alpha, degrees, degree_bound)
+                            })
+                            .collect::<Vec<E>>()
+                    }
+                    (0, None, Some(z), Some(value)) => {
+                        let points = indices.iter().map(|i| domain[*i]).collect::<Vec<F>>();
+                        // z is never in the domain for an honest transcript
+                        let quotient = deep_quotient(&points, &evaluations, &z, &value)
+                            .ok_or((layer, FriRejectionReason::FoldingMismatch))?;
+                        match self.degree_correction {
+                            Some((alpha, quotient_bound)) => {
+                                let degree_bound = dom_size / self.config.blowup_factor;
+                                quotient
+                                    .iter()
+                                    .zip(&points)
+                                    .map(|(q, x)| {
+                                        degree_correction(
+                                            q,
+                                            x,
+                                            &alpha,
+                                            quotient_bound,
+                                            degree_bound,
+                                        )
+                                    })
+                                    .collect()
+                            }
+                            None => quotient,
+                        }
+                    }
+                    _ => evaluations,
+                };
+
+            // The folding starts from the masked codeword
+            if let (0, Some(gamma)) = (layer, self.mask_challenge) {
+                evaluations = evaluations
+                    .iter()
+                    .zip(&masks)
+                    .map(|(value, mask)| *value + gamma * *mask)
+                    .collect();
+            }
+
+            if layer < self.betas.len() {
+                // f_next(x^k) is the polynomial interpolating f on the coset of x, taken at beta
+                let folded = fold_coset(&evaluations, &domain[indices[0]], &self.betas[layer])
+                    .ok_or((layer, FriRejectionReason::FoldingMismatch))?;
+
+                let next_domain = &self.layers_domains[layer + 1];
+                let next_size = next_domain.size();
+
+                if layer + 1 < number_of_layers {
+                    let next_evaluation = layers_evaluations[layer + 1]
+                        .get(position_in_coset(
+                            query_index,
+                            next_size,
+                            self.config.folding_factor,
+                        ))
+                        .ok_or((layer + 1, FriRejectionReason::MalformedDecommitment))?;
+
+                    if folded != *next_evaluation {
+                        return Err((layer, FriRejectionReason::FoldingMismatch));
+                    }
+                } else {
+                    // The last committed layer folds into the final polynomial
+                    let x = next_domain[query_index % next_size];
+                    if folded != self.last_poly.evaluate(&E::from_base(x)) {
+                        return Err((layer, FriRejectionReason::FinalLayerMismatch));
+                    }
+                }
+            } else if indices.iter().zip(&evaluations).any(|(index, evaluation)| {
+                // No folding round : the initial layer is the final polynomial
+                *evaluation != self.last_poly.evaluate(&E::from_base(domain[*index]))
+            }) {
+                return Err((layer, FriRejectionReason::FinalLayerMismatch));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Fold the evaluations of f on the coset x * <zeta> of the k-th roots of unity with beta,
+// None if x is zero
+// f(x * zeta^j) = sum_m (x * zeta^j)^m * f_m(x^k) so the polynomial interpolating the
+// evaluations on the coset has the coefficients f_m(x^k), its value at beta is the fold
+// For k = 2 : (f(x) + f(-x)) / 2 + beta * (f(x) - f(-x)) / (2x)
+fn fold_coset<F: TwoAdicField, E: ExtensionField<F>>(
+    evaluations: &[E],
+    x: &F,
+    beta: &E,
+) -> Option<E> {
+    if *x == F::ZERO || !evaluations.len().is_power_of_two() {
+        return None;
+    }
+    let coset = Domain::new_coset(evaluations.len(), *x);
+
+    Some(Polynome::interpolate_from_domain(evaluations, &coset).evaluate(beta))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::channel::FRI_PROTOCOL_LABEL;
+    use crate::field_extension::{CubicExtension, QuadraticExtension, QuarticExtension};
+    use crate::field_provider_babybear::BabyBear;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+    use crate::fri_code_layer::{evaluate_on_enlarged_domain, FriCodeLayer};
+    use crate::fri_proof::FriProofError;
+    use crate::hasher::{Blake3, Keccak256, Poseidon2};
+    use ff::Field;
+
+    // Default configuration with the given number of queries
+    fn queries_config(number_of_queries: usize) -> FriConfig {
+        FriConfig {
+            number_of_queries,
+            ..FriConfig::default()
+        }
+    }
+
+    fn prove<F: TwoAdicField>(
+        coefficients: &[F],
+        domain_size: usize,
+        number_of_queries: usize,
+    ) -> (Polynome<F>, FriDecommitment<F>, Vec<usize>, Channel<F>) {
+        prove_in_extension(
+            coefficients,
+            domain_size,
+            &queries_config(number_of_queries),
+        )
+    }
+
+    // Codeword in F, challenges and folded layers in E
+    fn prove_in_extension<F: TwoAdicField, E: ExtensionField<F>>(
+        coefficients: &[F],
+        domain_size: usize,
+        config: &FriConfig,
+    ) -> (Polynome<E>, FriDecommitment<E>, Vec<usize>, Channel<E>) {
+        let poly = Polynome::new_poly(coefficients);
+        let mut channel = Channel::new();
+        let (last_poly, fri_layers) =
+            FriCodeLayer::fri_commit_phase(poly, domain_size, config, &mut channel);
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(config, domain_size, &fri_layers, &mut channel);
+        (last_poly, decom, queries, channel)
+    }
+
+    fn check_honest_proof<F: TwoAdicField, E: ExtensionField<F>>(folding_factor: usize) {
+        let coefficients = (1..=7).map(|i| F::from(i * i)).collect::<Vec<F>>();
+        let domain_size = 64;
+        let config = FriConfig {
+            folding_factor,
+            ..queries_config(10)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<F, E>(&coefficients, domain_size, &config);
+
+        let verifier = FriVerifier::<F, E>::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        let mut wrong_decom = decom.clone();
+        wrong_decom.queries_evaluations[0].last_mut().unwrap()[0] += E::ONE;
+        assert!(!verifier.verify(&wrong_decom, &queries).is_accepted());
+    }
+
+    // Field provided by a user of the crate : p = 7 * 2^26 + 1
+    mod user_field {
+        use ff::PrimeField;
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "469762049"]
+        #[PrimeFieldGenerator = "3"]
+        #[PrimeFieldReprEndianness = "little"]
+        pub struct UserField([u64; 1]);
+    }
+
+    #[test]
+    fn test_verify_over_several_fields() {
+        check_honest_proof::<FieldElement, FieldElement>(2);
+        check_honest_proof::<BabyBear, BabyBear>(2);
+        check_honest_proof::<Goldilocks, Goldilocks>(2);
+        check_honest_proof::<user_field::UserField, user_field::UserField>(2);
+    }
+
+    #[test]
+    fn test_verify_with_extension_challenges() {
+        check_honest_proof::<Goldilocks, QuadraticExtension<Goldilocks>>(2);
+        check_honest_proof::<Goldilocks, CubicExtension<Goldilocks>>(2);
+        check_honest_proof::<BabyBear, QuarticExtension<BabyBear>>(2);
+    }
+
+    #[test]
+    fn test_verify_with_folding_factor() {
+        check_honest_proof::<FieldElement, FieldElement>(4);
+        check_honest_proof::<Goldilocks, Goldilocks>(8);
+        check_honest_proof::<Goldilocks, QuadraticExtension<Goldilocks>>(16);
+        check_honest_proof::<BabyBear, QuarticExtension<BabyBear>>(4);
+    }
+
+    #[test]
+    fn test_folding_factor_larger_than_layer() {
+        // Degree bound 4 on a domain of 8 points, folded by 16 in a single round
+        let coefficients = (1..=3).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 8;
+        let config = FriConfig::new(2, 4, 16, 1, 0).unwrap();
+        assert_eq!(config.number_of_rounds(domain_size), 1);
+
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, Goldilocks>(&coefficients, domain_size, &config);
+        assert_eq!(last_poly.degree(), Some(0));
+        // The whole layer is the coset of every query
+        assert_eq!(decom.queries_evaluations[0][0].len(), domain_size);
+
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        let mut wrong_decom = decom.clone();
+        wrong_decom.queries_evaluations[0][0][1] += Goldilocks::ONE;
+        assert!(!verifier.verify(&wrong_decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_folding_factor_reduces_layers() {
+        let coefficients = (1..=64).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 512;
+        let (_, binary_decom, _, binary_channel) = prove(&coefficients, domain_size, 4);
+        let config = FriConfig {
+            folding_factor: 4,
+            ..queries_config(4)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, Goldilocks>(&coefficients, domain_size, &config);
+
+        // Degree bounds 64, 16, 4 committed instead of 64, 32, ..., 2
+        assert_eq!(channel.get_committed_roots().len(), 3);
+        assert_eq!(binary_channel.get_committed_roots().len(), 6);
+        assert_eq!(decom.queries_evaluations[0][0].len(), 4);
+
+        assert!(decom.auth_paths_len() < binary_decom.auth_paths_len());
+
+        // Another folding factor gives other domains and other cosets
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert!(!verifier.verify(&decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_verify_proof_of_work() {
+        let coefficients = (1..=7)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let domain_size = 64;
+        let config = FriConfig {
+            grinding_bits: 8,
+            ..queries_config(4)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<FieldElement, FieldElement>(&coefficients, domain_size, &config);
+        let mut proof = FriProof::new(&channel, &last_poly, decom, queries);
+
+        let verifier = FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &proof);
+        assert!(verifier.verify_proof(&proof).is_accepted());
+
+        // The queries depend on the nonce, so they are derived again from another one
+        proof.pow_nonce += 1;
+        let verifier = FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &proof);
+        assert_eq!(
+            verifier.verify_proof(&proof),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::InvalidProofOfWork,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_first_layer_outside_base_field() {
+        type E = QuadraticExtension<Goldilocks>;
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 64;
+        let (last_poly, mut decom, queries, channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &queries_config(4));
+        let verifier = FriVerifier::<Goldilocks, E>::new(
+            domain_size,
+            &queries_config(4),
+            &channel,
+            &last_poly,
+        );
+
+        decom.queries_evaluations[0][0][0] += E::x();
+
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::MalformedDecommitment
+            }
+        );
+    }
+
+    #[test]
+    fn test_fold_coset() {
+        // f(x) = 1 + 2x + 3x^2 + 4x^3 folds into 1 + 3x + beta * (2 + 4x)
+        let poly = Polynome::new_poly(&[
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+            FieldElement::from(4u64),
+        ]);
+        let beta = FieldElement::from(5u64);
+        let x = FieldElement::from(11u64);
+
+        let folded = fold_coset(&[poly.evaluate(&x), poly.evaluate(&-x)], &x, &beta);
+        assert_eq!(folded, Some(poly.fold_with_beta(&beta).evaluate(&(x * x))));
+
+        // Coset of the 4-th roots of unity
+        let coset = Domain::new_coset(4, x);
+        let folded = fold_coset(&poly.evaluate_sliding(coset.elements()), &x, &beta);
+        assert_eq!(
+            folded,
+            Some(poly.fold_with_factor(&beta, 4).evaluate(&x.pow([4u64])))
+        );
+
+        assert_eq!(
+            fold_coset(
+                &[FieldElement::ONE, FieldElement::ONE],
+                &FieldElement::ZERO,
+                &beta
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_verify_constant_polynome() {
+        let domain_size = 16;
+        let (last_poly, decom, queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Accept
+        );
+    }
+
+    #[test]
+    fn test_verify_honest_proof() {
+        let coefficients = vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+            FieldElement::from(3u64),
+            FieldElement::from(3u64),
+            FieldElement::from(3u64),
+            FieldElement::from(3u64),
+        ];
+        let domain_size = 64;
+        let (last_poly, decom, queries, channel) = prove(&coefficients, domain_size, 20);
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(20), &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_verify_decoded_proof() {
+        let coefficients = vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+            FieldElement::from(4u64),
+        ];
+        let domain_size = 32;
+        let config = queries_config(6);
+        let (last_poly, decom, queries, channel) = prove(&coefficients, domain_size, 6);
+
+        let bytes = FriProof::new(&channel, &last_poly, decom, queries).to_bytes();
+        let proof = FriProof::<FieldElement>::from_bytes(&bytes).unwrap();
+
+        let verifier = FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &proof);
+        assert!(verifier.verify_proof(&proof).is_accepted());
+
+        let mut wrong_proof = proof.clone();
+        wrong_proof.last_poly_coefficients[0] += FieldElement::ONE;
+        let verifier =
+            FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &wrong_proof);
+        assert!(!verifier.verify_proof(&wrong_proof).is_accepted());
+    }
+
+    #[test]
+    fn test_reject_folding_with_another_beta() {
+        // Cheating prover folding with another beta than the transcript one
+        let poly = Polynome::new_poly(&[
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+            FieldElement::from(4u64),
+        ]);
+        let domain_size = 32;
+        let config = queries_config(4);
+        let mut channel = Channel::<FieldElement>::new();
+
+        let domain = generate_enlarged_evaluation_domain(domain_size);
+        let first_layer = FriCodeLayer::<_, _, Sha256>::new(&poly, &domain);
+        channel.add_committed_data(FieldElement::ZERO, first_layer.get_merkle_root());
+
+        let beta = channel.get_challenge();
+        let next_poly = poly.fold_with_beta(&(beta + FieldElement::ONE));
+        let next_layer = FriCodeLayer::new(&next_poly, &build_next_domain(&domain, 2));
+        channel.add_committed_data(beta, next_layer.get_merkle_root());
+
+        let last_poly = next_poly.fold_with_beta(&channel.get_challenge());
+        channel.add_final_polynomial(&last_poly.coefficients);
+
+        let fri_layers = vec![first_layer, next_layer];
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::FoldingMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_query_not_from_transcript() {
+        let domain_size = 16;
+        let (last_poly, decom, mut queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        queries[1] = (queries[1] + 1) % domain_size;
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 1,
+                layer: 0,
+                reason: FriRejectionReason::QueryIndexMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_with_protocol_label() {
+        let coefficients = vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+        ];
+        let domain_size = 32;
+        let config = queries_config(8);
+        let poly = Polynome::new_poly(&coefficients);
+        let mut channel = Channel::new_with_label(b"another protocol");
+        let (last_poly, fri_layers) = FriCodeLayer::<_, _, Sha256>::fri_commit_phase(
+            poly,
+            domain_size,
+            &config,
+            &mut channel,
+        );
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        // Same commitments replayed under the default label
+        let mut default_channel = Channel::<FieldElement>::new();
+        channel
+            .get_committed_challenges()
+            .iter()
+            .for_each(|c| default_channel.add_committed_data(*c, channel.get_merkle_root(*c)));
+
+        let verifier = FriVerifier::new(domain_size, &config, &default_channel, &last_poly);
+        assert!(!verifier.verify(&decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_reject_tampered_evaluation() {
+        let domain_size = 16;
+        let (last_poly, mut decom, queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        decom.queries_evaluations[2][0][0] += FieldElement::ONE;
+
+        // The multi-proof of the layer covers all the queries, its failure is reported on
+        // the first one
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::InvalidAuthPath,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_tampered_coset_sibling() {
+        let domain_size = 16;
+        let (last_poly, mut decom, queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        decom.queries_evaluations[1][0][1] += FieldElement::ONE;
+
+        // No other query opens this coset, the multi-proof fails and reports the first query
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::InvalidAuthPath,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_tampered_multi_proof() {
+        let coefficients = (1..=7)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let domain_size = 64;
+        let (last_poly, mut decom, queries, channel) = prove(&coefficients, domain_size, 8);
+
+        decom.layers_auth_paths[1][0][0] ^= 1;
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(8), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 1,
+                reason: FriRejectionReason::InvalidAuthPath,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_wrong_final_polynome() {
+        let domain_size = 16;
+        let config = queries_config(4);
+        let (_last_poly, decom, queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        // The final polynomial is in the transcript, the queries do not match anymore
+        let wrong_last_poly = Polynome::new_poly(&[FieldElement::from(4u64)]);
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &wrong_last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::QueryIndexMismatch,
+            }
+        );
+
+        // Cheating prover sending the wrong final polynomial from the start
+        let poly = Polynome::new_poly(&[FieldElement::from(3u64)]);
+        let domain = generate_enlarged_evaluation_domain(domain_size);
+        let layer = FriCodeLayer::<_, _, Sha256>::new(&poly, &domain);
+        let mut channel = Channel::<FieldElement>::new();
+        channel.add_committed_data(FieldElement::ZERO, layer.get_merkle_root());
+        channel.get_challenge();
+        channel.add_final_polynomial(&wrong_last_poly.coefficients);
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &[layer], &mut channel);
+
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &wrong_last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::FinalLayerMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_early_termination() {
+        let coefficients = (1..=64).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 512;
+        let config = FriConfig {
+            final_degree_bound: 8,
+            ..queries_config(4)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, Goldilocks>(&coefficients, domain_size, &config);
+
+        // Degree bounds 64, 32, 16 committed, the final polynomial has degree 7
+        assert_eq!(channel.get_committed_roots().len(), 3);
+        assert_eq!(last_poly.degree(), Some(7));
+
+        let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        // The verifier expects another number of layers with another bound
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 3,
+                reason: FriRejectionReason::WrongNumberOfLayers,
+            }
+        );
+
+        let low_bound = FriConfig {
+            final_degree_bound: 4,
+            ..config
+        };
+        let verifier = FriVerifier::new(domain_size / 2, &low_bound, &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 3,
+                reason: FriRejectionReason::FinalDegreeTooLarge,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_missing_commitment() {
+        let domain_size = 16;
+        let (last_poly, decom, queries, _channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        let mut channel = Channel::<FieldElement>::new();
+        channel.add_committed_data(FieldElement::ZERO, None);
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::MissingCommitment,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_malformed_decommitment() {
+        let domain_size = 16;
+        let (last_poly, mut decom, queries, channel) =
+            prove(&[FieldElement::from(3u64)], domain_size, 4);
+
+        decom.queries_evaluations[3].clear();
+
+        let verifier = FriVerifier::new(domain_size, &queries_config(4), &channel, &last_poly);
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 3,
+                layer: 0,
+                reason: FriRejectionReason::MalformedDecommitment,
+            }
+        );
+        let mut truncated_decom = decom.clone();
+        truncated_decom.queries_evaluations.truncate(2);
+        assert!(!verifier.verify(&truncated_decom, &queries).is_accepted());
+    }
+
+    fn check_deep_proof<F: TwoAdicField, E: ExtensionField<F>>(
+        coefficients: &[F],
+        domain_size: usize,
+        config: &FriConfig,
+    ) {
+        let config = FriConfig {
+            deep: true,
+            ..*config
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<F, E>(coefficients, domain_size, &config);
+
+        let verifier = FriVerifier::<F, E>::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        let mut wrong_decom = decom.clone();
+        wrong_decom.queries_evaluations[0][0][0] += E::ONE;
+        assert!(!verifier.verify(&wrong_decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_verify_deep() {
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+
+        for folding_factor in [2, 4] {
+            let config = FriConfig {
+                folding_factor,
+                ..queries_config(10)
+            };
+            check_deep_proof::<Goldilocks, Goldilocks>(&coefficients, 64, &config);
+            check_deep_proof::<Goldilocks, QuadraticExtension<Goldilocks>>(
+                &coefficients,
+                64,
+                &config,
+            );
+        }
+
+        // No folding round : the quotient is the final polynomial
+        let config = FriConfig {
+            final_degree_bound: 8,
+            ..queries_config(4)
+        };
+        check_deep_proof::<Goldilocks, CubicExtension<Goldilocks>>(&coefficients, 64, &config);
+    }
+
+    #[test]
+    fn test_reject_deep_degree_too_large() {
+        // Degree 8 on a domain of size 64 with a blowup of 8 : the degree bound is 8
+        let coefficients = (1..=9).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 64;
+        let config = queries_config(10);
+        let deep_config = FriConfig {
+            deep: true,
+            ..config
+        };
+
+        for config in [config, deep_config] {
+            let (last_poly, decom, queries, channel) =
+                prove_in_extension::<Goldilocks, Goldilocks>(&coefficients, domain_size, &config);
+            let verifier = FriVerifier::new(domain_size, &config, &channel, &last_poly);
+            // The quotient of degree 7 is tested against the bound 7
+            assert!(!verifier.verify(&decom, &queries).is_accepted());
+        }
+    }
+
+    #[test]
+    fn test_reject_wrong_deep_evaluation() {
+        type E = QuadraticExtension<Goldilocks>;
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 64;
+        let config = FriConfig {
+            deep: true,
+            ..queries_config(10)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &config);
+        let value = channel.get_deep_evaluation().unwrap();
+
+        // f(z) is absorbed before the challenges
+        let verifier = FriVerifier::<Goldilocks, E>::new_from_roots(
+            domain_size,
+            &config,
+            FRI_PROTOCOL_LABEL,
+            channel.get_committed_roots(),
+            None,
+            Some(value + E::ONE),
+            &last_poly,
+        );
+        assert!(!verifier.verify(&decom, &queries).is_accepted());
+
+        let verifier = FriVerifier::<Goldilocks, E>::new_from_roots(
+            domain_size,
+            &config,
+            FRI_PROTOCOL_LABEL,
+            channel.get_committed_roots(),
+            None,
+            None,
+            &last_poly,
+        );
+        assert_eq!(
+            verifier.verify(&decom, &queries),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::MissingDeepEvaluation,
+            }
+        );
+
+        // The folding of f does not give the folding of its quotient
+        let plain_config = FriConfig {
+            deep: false,
+            ..config
+        };
+        let verifier =
+            FriVerifier::<Goldilocks, E>::new(domain_size, &plain_config, &channel, &last_poly);
+        assert!(!verifier.verify(&decom, &queries).is_accepted());
+    }
+
+    #[test]
+    fn test_verify_decoded_deep_proof() {
+        type E = CubicExtension<Goldilocks>;
+        let coefficients = (1..=12).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let config = FriConfig {
+            deep: true,
+            folding_factor: 4,
+            ..FriConfig::default()
+        };
+        let domain_size = config.domain_size(coefficients.len() - 1);
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &config);
+
+        let proof = FriProof::<Goldilocks, E>::new(&channel, &last_poly, decom, queries);
+        let received_proof = FriProof::<Goldilocks, E>::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(received_proof.deep_evaluation.is_some());
+
+        let verifier =
+            FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &received_proof);
+        assert!(verifier.verify_proof(&received_proof).is_accepted());
+    }
+
+    fn prove_batch<F: TwoAdicField, E: ExtensionField<F>>(
+        polys: &[Polynome<F>],
+        domain_size: usize,
+        config: &FriConfig,
+    ) -> FriProof<F, E> {
+        let mut channel = Channel::new();
+        let (last_poly, fri_layers) =
+            FriCodeLayer::fri_commit_batch(polys, domain_size, config, &mut channel);
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(config, domain_size, &fri_layers, &mut channel);
+        FriProof::new(&channel, &last_poly, decom, queries)
+    }
+
+    fn check_batch_proof<F: TwoAdicField, E: ExtensionField<F>>(
+        degrees: &[usize],
+        config: &FriConfig,
+    ) -> (usize, FriProof<F, E>) {
+        let polys = degrees
+            .iter()
+            .map(|degree| {
+                Polynome::new_poly(
+                    &(0..=*degree as u64)
+                        .map(|i| F::from(i + 1))
+                        .collect::<Vec<F>>(),
+                )
+            })
+            .collect::<Vec<Polynome<F>>>();
+        let domain_size = config.domain_size(*degrees.iter().max().unwrap());
+        let proof = prove_batch::<F, E>(&polys, domain_size, config);
+        let received_proof = FriProof::<F, E>::from_bytes(&proof.to_bytes()).unwrap();
+
+        let verifier = FriVerifier::for_batch(
+            domain_size,
+            config,
+            FRI_PROTOCOL_LABEL,
+            &received_proof,
+            degrees,
+        );
+        assert!(verifier.verify_proof(&received_proof).is_accepted());
+        (domain_size, received_proof)
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        type E = QuadraticExtension<Goldilocks>;
+        let degrees = [1, 6, 15, 9];
+
+        for folding_factor in [2, 4] {
+            let config = FriConfig {
+                folding_factor,
+                ..queries_config(10)
+            };
+            let (domain_size, proof) = check_batch_proof::<Goldilocks, E>(&degrees, &config);
+            let verifier =
+                FriVerifier::for_batch(domain_size, &config, FRI_PROTOCOL_LABEL, &proof, &degrees);
+
+            // Each query opens the 4 values of each coset point
+            assert_eq!(
+                proof.decommitment.queries_evaluations[0][0].len(),
+                4 * folding_factor
+            );
+            let mut wrong_decom = proof.decommitment.clone();
+            wrong_decom.queries_evaluations[0][0][1] += E::ONE;
+            assert_eq!(
+                verifier.verify(&wrong_decom, &proof.queries),
+                FriVerificationResult::Reject {
+                    query: 0,
+                    layer: 0,
+                    reason: FriRejectionReason::InvalidAuthPath,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_with_zero_polynomial() {
+        let config = queries_config(10);
+        let p = Polynome::new_poly(&[FieldElement::from(1u64), FieldElement::from(2u64)]);
+        let polys = [&p - &p, p.clone()];
+        let domain_size = config.domain_size(1);
+        let proof = prove_batch::<FieldElement, FieldElement>(&polys, domain_size, &config);
+
+        let verifier =
+            FriVerifier::for_batch(domain_size, &config, FRI_PROTOCOL_LABEL, &proof, &[0, 1]);
+        assert!(verifier.verify_proof(&proof).is_accepted());
+    }
+
+    #[test]
+    fn test_reject_batch_degree_too_large() {
+        let config = queries_config(10);
+        let (domain_size, proof) =
+            check_batch_proof::<FieldElement, FieldElement>(&[3, 12], &config);
+
+        // The second polynomial has a degree larger than the claimed one
+        let verifier =
+            FriVerifier::for_batch(domain_size, &config, FRI_PROTOCOL_LABEL, &proof, &[3, 4]);
+        assert!(!verifier.verify_proof(&proof).is_accepted());
+
+        // The single FRI verifier does not sample the combination challenge
+        let verifier = FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &proof);
+        assert_eq!(
+            verifier.verify_proof(&proof),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::QueryIndexMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_coset_leaves() {
+        type E = QuadraticExtension<Goldilocks>;
+        let coefficients = (1..=30).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 256;
+
+        for folding_factor in [2, 4, 8] {
+            let config = FriConfig {
+                folding_factor,
+                ..queries_config(10)
+            };
+            let coset_config = FriConfig {
+                coset_leaves: true,
+                ..config
+            };
+            let (last_poly, decom, queries, channel) =
+                prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &coset_config);
+            let verifier =
+                FriVerifier::<Goldilocks, E>::new(domain_size, &coset_config, &channel, &last_poly);
+            assert!(verifier.verify(&decom, &queries).is_accepted());
+
+            // Same layers, but a single leaf per layer and shorter auth paths
+            let (_, plain_decom, _, _) =
+                prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &config);
+            assert_eq!(
+                decom.queries_evaluations[0].len(),
+                plain_decom.queries_evaluations[0].len()
+            );
+            assert!(decom.auth_paths_len() < plain_decom.auth_paths_len());
+
+            let mut wrong_decom = decom.clone();
+            wrong_decom.queries_evaluations[0][0][folding_factor - 1] += E::ONE;
+            assert_eq!(
+                verifier.verify(&wrong_decom, &queries),
+                FriVerificationResult::Reject {
+                    query: 0,
+                    layer: 0,
+                    reason: FriRejectionReason::InvalidAuthPath,
+                }
+            );
+
+            // The layout of the leaves is part of the configuration
+            let verifier =
+                FriVerifier::<Goldilocks, E>::new(domain_size, &config, &channel, &last_poly);
+            assert!(!verifier.verify(&decom, &queries).is_accepted());
+        }
+    }
+
+    #[test]
+    fn test_verify_merkle_caps() {
+        type E = QuadraticExtension<Goldilocks>;
+        let coefficients = (1..=30).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let domain_size = 256;
+        // A single query opens x and -x in both halves of each layer of 2^d leaves, with
+        // one path of d - k hashes per point under a cap of height k >= 1
+        let config = queries_config(1);
+
+        for merkle_cap_height in [1, 3, 6] {
+            let cap_config = FriConfig {
+                merkle_cap_height,
+                ..config
+            };
+            let (last_poly, decom, queries, channel) =
+                prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &cap_config);
+            let verifier =
+                FriVerifier::<Goldilocks, E>::new(domain_size, &cap_config, &channel, &last_poly);
+            assert!(verifier.verify(&decom, &queries).is_accepted());
+            assert_eq!(
+                decom.auth_paths_len(),
+                (4..=8)
+                    .map(|d: usize| 2 * d.saturating_sub(merkle_cap_height))
+                    .sum::<usize>()
+            );
+
+            // The cap of a layer is at most its number of leaves
+            let proof = FriProof::<Goldilocks, E>::new(&channel, &last_poly, decom, queries);
+            for (layer, cap) in proof.layers_caps.iter().enumerate() {
+                let leaves_count = domain_size >> layer;
+                assert_eq!(cap.len(), (1 << merkle_cap_height).min(leaves_count));
+            }
+
+            let received_proof = FriProof::<Goldilocks, E>::from_bytes(&proof.to_bytes()).unwrap();
+            let verifier = FriVerifier::<Goldilocks, E>::from_proof(
+                domain_size,
+                &cap_config,
+                FRI_PROTOCOL_LABEL,
+                &received_proof,
+            );
+            assert!(verifier.verify_proof(&received_proof).is_accepted());
+
+            let mut wrong_proof = received_proof.clone();
+            wrong_proof.layers_caps[0][1][0] ^= 1;
+            let verifier = FriVerifier::<Goldilocks, E>::from_proof(
+                domain_size,
+                &cap_config,
+                FRI_PROTOCOL_LABEL,
+                &wrong_proof,
+            );
+            assert!(!verifier.verify_proof(&wrong_proof).is_accepted());
+
+            // The cap height is part of the configuration
+            let verifier = FriVerifier::<Goldilocks, E>::from_proof(
+                domain_size,
+                &config,
+                FRI_PROTOCOL_LABEL,
+                &received_proof,
+            );
+            assert_eq!(
+                verifier.verify_proof(&received_proof),
+                FriVerificationResult::Reject {
+                    query: 0,
+                    layer: 0,
+                    reason: FriRejectionReason::WrongCapSize,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_coset_leaves_deep_and_batch() {
+        type E = QuadraticExtension<Goldilocks>;
+        let config = FriConfig {
+            folding_factor: 4,
+            coset_leaves: true,
+            ..queries_config(10)
+        };
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        check_deep_proof::<Goldilocks, E>(&coefficients, 64, &config);
+
+        check_batch_proof::<Goldilocks, E>(&[2, 11], &config);
+    }
+
+    fn check_hasher<H: MerkleHasher>() -> [u8; 32] {
+        type E = QuadraticExtension<Goldilocks>;
+        let poly = Polynome::new_poly(&(1..=9).map(Goldilocks::from).collect::<Vec<Goldilocks>>());
+        let config = FriConfig {
+            folding_factor: 4,
+            ..queries_config(12)
+        };
+        let domain_size = config.domain_size(poly.degree().unwrap());
+
+        let mut channel = Channel::new();
+        let (last_poly, fri_layers) = FriCodeLayer::<Goldilocks, E, H>::fri_commit_phase(
+            poly,
+            domain_size,
+            &config,
+            &mut channel,
+        );
+        let (decom, queries) =
+            FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, &mut channel);
+        let bytes =
+            FriProof::<Goldilocks, E, H>::new(&channel, &last_poly, decom, queries).to_bytes();
+
+        let proof = FriProof::<Goldilocks, E, H>::from_bytes(&bytes).unwrap();
+        let verifier = FriVerifier::<Goldilocks, E, H>::from_proof(
+            domain_size,
+            &config,
+            FRI_PROTOCOL_LABEL,
+            &proof,
+        );
+        assert!(verifier.verify_proof(&proof).is_accepted());
+
+        proof.layers_caps[0][0]
+    }
+
+    #[test]
+    fn test_verify_with_hashers() {
+        let roots = [
+            check_hasher::<Sha256>(),
+            check_hasher::<Blake3>(),
+            check_hasher::<Keccak256>(),
+            check_hasher::<Poseidon2>(),
+        ];
+        assert!((0..roots.len()).all(|i| (0..i).all(|j| roots[i] != roots[j])));
+
+        // A verifier using another hash function does not accept the proof
+        let coefficients = (1..=7)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let (last_poly, decom, queries, channel) = prove(&coefficients, 64, 4);
+        let bytes = FriProof::<FieldElement>::new(&channel, &last_poly, decom, queries).to_bytes();
+        assert!(matches!(
+            FriProof::<FieldElement, FieldElement, Blake3>::from_bytes(&bytes),
+            Err(FriProofError::HasherMismatch(0))
+        ));
+    }
+
+    #[test]
+    fn test_verify_zero_knowledge() {
+        type E = QuadraticExtension<Goldilocks>;
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        let config = FriConfig {
+            zero_knowledge: true,
+            ..queries_config(10)
+        };
+        // Room for the 20 points opened in the first layer
+        let domain_size = config.domain_size(6);
+        assert_eq!(domain_size, 256);
+
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &config);
+        let verifier =
+            FriVerifier::<Goldilocks, E>::new(domain_size, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+
+        // One salt per opened leaf, the first layer rows hold f(x) and the mask
+        assert!(decom.queries_salts[0].iter().all(|salts| salts.len() == 2));
+        assert_eq!(decom.queries_evaluations[0][0].len(), 4);
+
+        // The first layer commits the blinded f, none of the opened values is a value of f
+        let domain = generate_enlarged_evaluation_domain::<Goldilocks>(domain_size);
+        let codeword = evaluate_on_enlarged_domain::<Goldilocks, Goldilocks>(
+            &Polynome::new_poly(&coefficients),
+            &domain,
+        );
+        for (query, evaluations) in queries.iter().zip(&decom.queries_evaluations) {
+            let indices = coset_indices(*query, domain_size, config.folding_factor);
+            for (index, row) in indices.iter().zip(evaluations[0].chunks(2)) {
+                assert_ne!(row[0], E::from_base(codeword[*index]));
+            }
+        }
+
+        let proof = FriProof::<Goldilocks, E>::new(&channel, &last_poly, decom, queries);
+        let received_proof = FriProof::<Goldilocks, E>::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(received_proof, proof);
+        let verifier = FriVerifier::<Goldilocks, E>::from_proof(
+            domain_size,
+            &config,
+            FRI_PROTOCOL_LABEL,
+            &received_proof,
+        );
+        assert!(verifier.verify_proof(&received_proof).is_accepted());
+
+        // Salts and mask are drawn again for each proof of the same polynomial
+        let (other_last_poly, _, _, other_channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, domain_size, &config);
+        assert_ne!(
+            other_channel.get_committed_roots()[0],
+            channel.get_committed_roots()[0]
+        );
+        assert_ne!(other_last_poly.coefficients, last_poly.coefficients);
+
+        let mut wrong_salt = proof.clone();
+        wrong_salt.decommitment.queries_salts[0][1][0][0] ^= 1;
+        assert_eq!(
+            verifier.verify_proof(&wrong_salt),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 1,
+                reason: FriRejectionReason::InvalidAuthPath,
+            }
+        );
+
+        // The first layer mask is folded with f
+        let mut wrong_mask = proof.clone();
+        wrong_mask.decommitment.queries_evaluations[0][0][1] += E::ONE;
+        assert!(!verifier.verify_proof(&wrong_mask).is_accepted());
+
+        // The zero-knowledge mode is part of the configuration, the transcript replayed
+        // without the masking challenge derives other queries
+        let verifier = FriVerifier::<Goldilocks, E>::from_proof(
+            domain_size,
+            &queries_config(10),
+            FRI_PROTOCOL_LABEL,
+            &proof,
+        );
+        assert_eq!(
+            verifier.verify_proof(&proof),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::QueryIndexMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_zero_knowledge_with_other_modes() {
+        type E = QuadraticExtension<Goldilocks>;
+        let config = FriConfig {
+            folding_factor: 4,
+            coset_leaves: true,
+            merkle_cap_height: 2,
+            zero_knowledge: true,
+            ..queries_config(6)
+        };
+        let coefficients = (1..=7).map(Goldilocks::from).collect::<Vec<Goldilocks>>();
+        check_deep_proof::<Goldilocks, E>(&coefficients, config.domain_size(6), &config);
+
+        check_batch_proof::<Goldilocks, E>(&[2, 11], &config);
+
+        // No folding round : the masked codeword is the final polynomial
+        let config = FriConfig {
+            final_degree_bound: 64,
+            zero_knowledge: true,
+            ..queries_config(4)
+        };
+        let (last_poly, decom, queries, channel) =
+            prove_in_extension::<Goldilocks, E>(&coefficients, 512, &config);
+        let verifier = FriVerifier::<Goldilocks, E>::new(512, &config, &channel, &last_poly);
+        assert!(verifier.verify(&decom, &queries).is_accepted());
+    }
+}
@@ -0,0 +1,44 @@
+// Subject: Hash functions of the Merkle commitments
+//
+// The layers, the decommitments and the verifier are generic over a `MerkleHasher`, a
+// rs_merkle hasher with 32 bytes digests and an identifier written in the proofs, so
+// that a proof is only decoded with the hash function it was made with.
+// SHA-256 is the default. BLAKE3 is faster natively, Keccak-256 is cheap on Ethereum
+// and Poseidon2 is an algebraic hash over the BLS12-381 scalar field, cheap in a circuit
+// verifying the proof recursively.
+// The Fiat-Shamir transcript of the channel runs the sponge of the same function.
+
+use std::fmt::Debug;
+
+use rs_merkle::Hasher;
+
+pub use crate::hasher_blake3::Blake3;
+pub use crate::hasher_keccak::Keccak256;
+pub use crate::hasher_poseidon2::Poseidon2;
+use crate::transcript::{HashChain, TranscriptSponge};
+
+pub trait MerkleHasher: Hasher<Hash = [u8; 32]> + Copy + Debug + PartialEq + Eq {
+    // Identifier of the hash function in the proof encoding
+    const ID: u8;
+
+    // Sponge of the Fiat-Shamir transcript
+    type Sponge: TranscriptSponge;
+}
+
+// SHA-256 of rs_merkle, with the comparisons the proof types derive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sha256;
+
+impl Hasher for Sha256 {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        rs_merkle::algorithms::Sha256::hash(data)
+    }
+}
+
+impl MerkleHasher for Sha256 {
+    const ID: u8 = 0;
+
+    type Sponge = HashChain<Self>;
+}
@@ -0,0 +1,50 @@
+// Subject: BLAKE3 hash
+//
+// Merkle hasher over the reference `blake3` crate, which picks the SIMD implementation
+// of the running CPU.
+
+use rs_merkle::Hasher;
+
+use crate::hasher::MerkleHasher;
+use crate::transcript::HashChain;
+
+pub fn blake3(data: &[u8]) -> [u8; 32] {
+    *blake3::hash(data).as_bytes()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Blake3;
+
+impl Hasher for Blake3 {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        blake3(data)
+    }
+}
+
+impl MerkleHasher for Blake3 {
+    const ID: u8 = 1;
+
+    type Sponge = HashChain<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_blake3_known_answers() {
+        assert_eq!(
+            hex::encode(blake3(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        // Official test vector of 1025 bytes i mod 251, two chunks of the BLAKE3 tree
+        let input = (0..1025).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        assert_eq!(
+            hex::encode(blake3(&input)),
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"
+        );
+    }
+}
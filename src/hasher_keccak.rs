@@ -0,0 +1,51 @@
+// Subject: Keccak-256 hash
+//
+// Merkle hasher over `Keccak256` of the `sha3` crate: the original Keccak padding
+// 0x01 ... 0x80, as used by Ethereum (SHA3-256 differs by its 0x06 padding).
+
+use rs_merkle::Hasher;
+use sha3::Digest;
+
+use crate::hasher::MerkleHasher;
+use crate::transcript::HashChain;
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    sha3::Keccak256::digest(data).into()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keccak256;
+
+impl Hasher for Keccak256 {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        keccak256(data)
+    }
+}
+
+impl MerkleHasher for Keccak256 {
+    const ID: u8 = 2;
+
+    type Sponge = HashChain<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_keccak256_known_answers() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // 300 bytes i mod 251, three blocks of 136 bytes
+        let input = (0..300).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        assert_eq!(
+            hex::encode(keccak256(&input)),
+            "4699841dafd5e26cca72b05a41d38c96b4b468e5a6cbf694cbebe77dacdf6528"
+        );
+    }
+}
@@ -0,0 +1,497 @@
+// Subject: Poseidon2 algebraic hash over the BLS12-381 scalar field
+//
+// Poseidon2 permutation of width 3 with the S-box x -> x^5 : an initial external linear
+// layer, 4 full rounds, 56 partial rounds whose S-box applies to the first word only,
+// then 4 full rounds. The external matrix is circ(2, 1, 1), the internal one is
+// J + diag(1, 1, 2), both cheaper than the MDS matrix of Poseidon.
+// This is the instance of the reference implementation of the Poseidon2 paper for
+// BLS12-381 and t = 3, with its round constants and test vectors.
+// It is used in three modes :
+//   - a sponge of rate 2 and capacity 1, the capacity starting with the input length,
+//     which hashes bytes for the leaves,
+//   - a 2-to-1 compression of the Merkle nodes, the first word of the permutation of
+//     (left, right, 0) added to left,
+//   - a duplex sponge of rate 2 for the Fiat-Shamir transcript, which absorbs the
+//     digests and the field elements as words.
+
+use std::sync::OnceLock;
+
+use ff::{Field, PrimeField};
+use rs_merkle::Hasher;
+
+use crate::field::FriField;
+use crate::field_provider_v1::FieldElement;
+use crate::hasher::MerkleHasher;
+use crate::transcript::{TranscriptSponge, ABSORB_TAG, SQUEEZE_TAG};
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+// Rounds of the Poseidon2 paper for a 255 bits field and x^5 at 128 bits of security
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 56;
+// Bytes per absorbed element, below the 255 bits of p
+const CHUNK_LEN: usize = 31;
+
+// Round constants of the reference implementation for BLS12-381 and a width of 3, as
+// big endian hexadecimal strings : the full rounds, then the first word of the partial
+// rounds
+const FULL_ROUND_CONSTANTS: [[&str; WIDTH]; FULL_ROUNDS] = [
+    [
+        "6f007a551156b3a449e44936b7c093644a0ed33f33eaccc628e942e836c1a875",
+        "360d7470611e473d353f628f76d110f34e71162f31003b7057538c2596426303",
+        "4b5fec3aa073df44019091f007a44ca996484965f7036dce3e9d0977edcdc0f6",
+    ],
+    [
+        "67cf1868af6396c0b84cce715e539f849e06cd1c383ac5b06100c76bcc973a11",
+        "555db4d1dced819f5d3de70fde83f1c7d3e8c98968e516a23a771a5c9c8257aa",
+        "2bab94d7ae222d135dc3c6c5febfaa314908ac2f12ebe06fbdb74213bf63188b",
+    ],
+    [
+        "66f44be5296682c4fa7882799d6dd049b6d7d2c950ccf98cf2e50d6d1ebb77c2",
+        "150c93fef652fb1c2bf03e1a29aa871fef77e7d736766c5d0939d92753cc5dc8",
+        "3270661e68928b3a955d55db56dc57c103cc0a60141e894e14259dce537782b2",
+    ],
+    [
+        "073f116f04122e25a0b7afe4e2057299b407c370f2b5a1ccce9fb9ffc345afb3",
+        "409fda22558cfe4d3dd8dce24f69e76f8c2aaeb1dd0f09d65e654c71f32aa23f",
+        "2a32ec5c4ee5b1837affd09c1f53f5fd55c9cd2061ae93ca8ebad76fc71554d8",
+    ],
+    [
+        "6cbac5e1700984ebc32da15b4bb9683faabab55f67ccc4f71d9560b3475a77eb",
+        "4603c403bbfa9a17738a5c6278eaab1c37ec30b0737aa2409fc4898069eb983c",
+        "6894e7e22b2c1d5c70a712a6345ae6b192a9c833a9234c31c56aacd16bc2f100",
+    ],
+    [
+        "5be2cbbc44053ad08afa4d1eabc7f3d231eea799b93f226e905b7d4d65c58ebb",
+        "58e55f287b453a9808624a8c2a353d528da0f7e713a5c6d0d7711e47063fa611",
+        "366ebfafa3ad381c0ee258c9b8fdfccdb868a7d7e1f1f69a2b5dfcc5572555df",
+    ],
+    [
+        "45766ab728968c642f90d97ccf5504ddc10518a819ebbcc4d09c3f5d784d67ce",
+        "39678f65512f1ee404db3024f41d3f567ef66d89d044d022e6bc229e95bc76b1",
+        "463aed1d2f1f955e3078be5bf7bfc46fc0eb8c51551906a8868f18ffae30cf4f",
+    ],
+    [
+        "21668f016a8063c0d58b7750a3bc2fe1cf82c25f99dc01a4e534c88fe53d85fe",
+        "39d00994a8a5046a1bc749363e98a768e34dea56439fe1954bef429bc5331608",
+        "4d7f5dcd78ece9a933984de32c0b48fac2bba91f261996b8e9d1021773bd07cc",
+    ],
+];
+const PARTIAL_ROUND_CONSTANTS: [&str; PARTIAL_ROUNDS] = [
+    "5848ebeb5923e92555b7124fffba5d6bd571c6f984195eb9cfd3a3e8eb55b1d4",
+    "270326ee039df19e651e2cfc740628ca634d24fc6e2559f22d8ccbe292efeead",
+    "27c6642ac633bc66dc100fe7fcfa54918af895bce012f182a068fc37c182e274",
+    "1bdfd8b01401c70ad27f57396989129d710e1fb6ab976a459ca18682e26d7ff9",
+    "491b9ba6983bcf9f05fe4794adb44a30879bf8289662e1f57d90f672414e8a4a",
+    "162a14c62f9a89b814b9d6a9c84dd678f4f6fb3f9054d373c832d824261a35ea",
+    "2d193e0f76de586b2af6f79e3127feeaac0a1fc71e2cf0c0f79824667b5b6bec",
+    "46efd8a9a262d6d8fdc9ca5c04b0982f24ddcc6e9863885a6a732a3906a07b95",
+    "509717e0c200e3c92d8dca2973b3db45f0788294351ad07ae75cbb780693a798",
+    "7299b28464a8c94fb9d4df61380f39c0dca9c2c014118789e227252820f01bfc",
+    "044ca3cc4a85d73b81696ef1104e674f4feff82984990ff85d0bf58dc8a4aa94",
+    "1cbaf2b371dac6a81d0453416d3e235cb8d9e2d4f314f46f6198785f0cd6b9af",
+    "1d5b2777692c205b0e6c49d061b6b5f4293c4ab038fdbbdc343e07610f3fede5",
+    "56ae7c7a5293bdc23e85e1698c81c77f8ad88c4b33a5780437ad047c6edb59ba",
+    "2e9bdbba3dd34bffaa30535bdd749a7e06a9adb0c1e6f962f60e971b8d73b04f",
+    "2de11886b18011ca8bd5bae36969299fde40fbe26d047b05035a13661f22418b",
+    "2e07de1780b8a70d0d5b4a3f1841dcd82ab9395c449be947bc998884ba96a721",
+    "0f69f1854d20ca0cbbdb63dbd52dad16250440a99d6b8af3825e4c2bb74925ca",
+    "5dc987318e6e59c1afb87b655dd58cc1d22e513a05838cd4585d04b135b957ca",
+    "48b725758571c9df6c01dc639a85f07297696b1bb678633a29dc91de95ef53f6",
+    "5e565e08c0821099256b56490eaee1d573afd10bb6d17d13ca4e5c611b2a3718",
+    "2eb1b25417fe17670d135dc639fb09a46ce5113507f96de9816c059422dc705e",
+    "115cd0a0643cfb988c24cb44c3fab48aff36c661d26cc42db8b1bdf4953bd82c",
+    "26ca293f7b2c462d066d7378b999868bbb57ddf14e0f958ade801612311d04cd",
+    "4147400d8e1aaccf311a6b5b762011ab3e45326e4d4b9de26992816b99c528ac",
+    "6b0db7dccc4ba1b268f6bdcc4d372848d4a72976c268ea30519a2f73e6db4d55",
+    "17bf1b93c4c7e01a2a830aa162412cd90f160bf9f71e967ff5209d14b24820ca",
+    "4b431cd9efedbc94cf1eca6f9e9c1839d0e66a8bffa8c8464cac81a39d3cf8f1",
+    "35b41a7ac4f3c571a24f8456369c85dfe03c0354bd8cfd3805c86f2e7dc293c5",
+    "3b1480080523c439435927994849bea964e14d3beb2dddde72ac156af435d09e",
+    "2cc6810031dc1b0d4950856dc907d57508e286442a2d3eb2271618d874b14c6d",
+    "6f4141c8401c5a395ba6790efd71c70c04afea06c3c92826bcabdd5cb5477d51",
+    "25bdbbeda1bde8c1059618e2afd2ef999e517aa93b78341d91f318c09f0cb566",
+    "392a4a8758e06ee8b95f33c25dde8ac02a5ed0a27b61926cc6313487073f7f7b",
+    "272a55878a08442b9aa6111f4de009485e6a6fd15db89365e7bbcef02eb5866c",
+    "631ec1d6d28dd9e824ee89a30730aef7ab463acfc9d184b355aa05fd6938eab5",
+    "4eb6fda10fd0fbde02c7449bfbddc35bcd8225e7e5c3833a0818a100409dc6f2",
+    "2d5b308b0cf02cdfefa13c4e60e26239a6ebba011694dd129b925b3c5b21e0e2",
+    "16549fc6af2f3b72dd5d293d72e2e5f244dff42f18b46c56ef38c57c311673ac",
+    "42332677ff359c5e8db836d9f5fb54822e39bd5e22340bb9ba975ba1a92be382",
+    "49d7d2c0b449e5179bc5ccc3b44c6075d9849b5610465f09ea725ddc97723a94",
+    "64c20fb90d7a003831757cc4c6226f6e4985fc9ecb416b9f684ca0351d967904",
+    "59cff40de83b52b41bc443d7979510d771c940b9758ca820fe73b5c8d5580934",
+    "53db2731730c39b04edd875fe3b7c882808285cdbc621d7af4f80dd53ebb71b0",
+    "1b10bb7a82afce39fa69c3a2ad52f76d76398265344203119b7126d9b46860df",
+    "561b6012d666bfe179c4dd7f84cdd1531596d3aac7c5700ceb319f91046a63c9",
+    "0f1e7505ebd91d2fc79c2df7dc98a3bed1b36968ba0405c090d27f6a00b7dfc8",
+    "2f313faf0d3f6187537a7497a3b43f46797fd6e3f18eb1caff457756b819bb20",
+    "3a5cbb6de450b481fa3ca61c0ed15bc55cad11ebf0f7ceb8f0bc3e732ecb26f6",
+    "681d93411bf8ce63f6716aefbd0e24506454c0348ee38fabeb264702714ccf94",
+    "5178e940f50004312646b436727f0e80a7b8f2e9ee1fdc677c4831a7672777fb",
+    "3dab54bc9bef688dd92086e253b439d651baa6e20f892b62865527cbca915982",
+    "4b3ce75311218f9ae905f84eaa5b2b3818448bbf3972e1aad69de321009015d0",
+    "06dbfb42b979884de280d31670123f744c24b33b410fefd4368045acf2b71ae3",
+    "068d6b4608aae810c6f039ea1973a63eb8d2de72e3d2c9eca7fc32d22f18b9d3",
+    "4c5c254589a92a36084a57d3b1d964278acc7e4fe8f69f2955954f27a79cebef",
+];
+
+struct RoundConstants {
+    full: [[FieldElement; WIDTH]; FULL_ROUNDS],
+    partial: [FieldElement; PARTIAL_ROUNDS],
+}
+
+// The field encoding is little endian
+fn from_hex(constant: &str) -> FieldElement {
+    let mut bytes = hex::decode(constant).expect("Hexadecimal round constant");
+    bytes.reverse();
+    FieldElement::from_bytes(&bytes).expect("Round constants are below p")
+}
+
+fn round_constants() -> &'static RoundConstants {
+    static CONSTANTS: OnceLock<RoundConstants> = OnceLock::new();
+    CONSTANTS.get_or_init(|| RoundConstants {
+        full: FULL_ROUND_CONSTANTS.map(|round| round.map(from_hex)),
+        partial: PARTIAL_ROUND_CONSTANTS.map(from_hex),
+    })
+}
+
+fn sbox(x: FieldElement) -> FieldElement {
+    x.square().square() * x
+}
+
+// circ(2, 1, 1) : each word gets the sum of all the words added
+fn external_linear_layer(state: &mut [FieldElement; WIDTH]) {
+    let sum = state[0] + state[1] + state[2];
+    state.iter_mut().for_each(|word| *word += sum);
+}
+
+// J + diag(1, 1, 2)
+fn internal_linear_layer(state: &mut [FieldElement; WIDTH]) {
+    let sum = state[0] + state[1] + state[2];
+    state[0] += sum;
+    state[1] += sum;
+    state[2] = state[2].double() + sum;
+}
+
+fn full_round(state: &mut [FieldElement; WIDTH], constants: &[FieldElement; WIDTH]) {
+    for (word, constant) in state.iter_mut().zip(constants) {
+        *word = sbox(*word + constant);
+    }
+    external_linear_layer(state);
+}
+
+pub fn poseidon2_permutation(state: &mut [FieldElement; WIDTH]) {
+    let constants = round_constants();
+    let (first_full, last_full) = constants.full.split_at(FULL_ROUNDS / 2);
+
+    external_linear_layer(state);
+    first_full.iter().for_each(|round| full_round(state, round));
+    for constant in &constants.partial {
+        state[0] = sbox(state[0] + constant);
+        internal_linear_layer(state);
+    }
+    last_full.iter().for_each(|round| full_round(state, round));
+}
+
+// Elements absorbed by pairs, the last pair padded with zero
+pub fn poseidon2_sponge(elements: &[FieldElement]) -> FieldElement {
+    let mut state = [
+        FieldElement::ZERO,
+        FieldElement::ZERO,
+        FieldElement::from(elements.len() as u64),
+    ];
+    for chunk in elements.chunks(RATE) {
+        for (word, element) in state.iter_mut().zip(chunk) {
+            *word += element;
+        }
+        poseidon2_permutation(&mut state);
+    }
+    state[0]
+}
+
+pub fn poseidon2_compress(left: FieldElement, right: FieldElement) -> FieldElement {
+    let mut state = [left, right, FieldElement::ZERO];
+    poseidon2_permutation(&mut state);
+    state[0] + left
+}
+
+fn byte_chunks(data: &[u8]) -> Vec<FieldElement> {
+    data.chunks(CHUNK_LEN)
+        .map(|chunk| {
+            let mut repr = [0u8; 32];
+            repr[..chunk.len()].copy_from_slice(chunk);
+            FieldElement::from_bytes(&repr).expect("31 bytes are below p")
+        })
+        .collect()
+}
+
+// Bytes to field elements, injective thanks to the trailing length
+fn bytes_to_elements(data: &[u8]) -> Vec<FieldElement> {
+    let mut elements = byte_chunks(data);
+    elements.push(FieldElement::from(data.len() as u64));
+    elements
+}
+
+fn element_to_digest(element: FieldElement) -> [u8; 32] {
+    element
+        .to_bytes()
+        .try_into()
+        .expect("Field elements are 32 bytes long")
+}
+
+// A digest is the canonical encoding of a field element, other bytes are hashed first
+fn digest_to_element(digest: &[u8; 32]) -> FieldElement {
+    FieldElement::from_bytes(digest).unwrap_or_else(|| poseidon2_sponge(&bytes_to_elements(digest)))
+}
+
+// The coefficients of an element over the BLS12-381 scalar field are words of the sponge,
+// an element of another field is split in chunks of its encoding : a single word for the
+// 64 bits fields and their extensions of degree up to 3
+fn field_element_words<F: FriField>(element: &F) -> Vec<FieldElement> {
+    let bytes = element.to_bytes();
+    if F::prime_subfield_modulus() == FieldElement::MODULUS {
+        bytes
+            .chunks(FieldElement::encoded_len())
+            .map(|chunk| FieldElement::from_bytes(chunk).expect("Canonical coefficient encoding"))
+            .collect()
+    } else {
+        byte_chunks(&bytes)
+    }
+}
+
+// Duplex sponge of the transcript. A message is its tag, the length and the chunks of
+// its label, then its number of words and the words, absorbed by pairs with the last one
+// padded with zero. The lengths come first so that the messages split unambiguously.
+// A squeeze absorbs its label and outputs the first word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Poseidon2Sponge {
+    state: [FieldElement; WIDTH],
+}
+
+impl Default for Poseidon2Sponge {
+    fn default() -> Self {
+        Self {
+            state: [FieldElement::ZERO; WIDTH],
+        }
+    }
+}
+
+impl Poseidon2Sponge {
+    fn duplex(&mut self, tag: u8, label: &[u8], words: &[FieldElement]) {
+        let message = [
+            vec![
+                FieldElement::from(tag as u64),
+                FieldElement::from(label.len() as u64),
+            ],
+            byte_chunks(label),
+            vec![FieldElement::from(words.len() as u64)],
+            words.to_vec(),
+        ]
+        .concat();
+        for chunk in message.chunks(RATE) {
+            for (word, element) in self.state.iter_mut().zip(chunk) {
+                *word += element;
+            }
+            poseidon2_permutation(&mut self.state);
+        }
+    }
+}
+
+impl TranscriptSponge for Poseidon2Sponge {
+    fn absorb(&mut self, label: &[u8], data: &[u8]) {
+        self.duplex(ABSORB_TAG, label, &bytes_to_elements(data));
+    }
+
+    fn absorb_digests(&mut self, label: &[u8], digests: &[[u8; 32]]) {
+        let words = digests
+            .iter()
+            .map(digest_to_element)
+            .collect::<Vec<FieldElement>>();
+        self.duplex(ABSORB_TAG, label, &words);
+    }
+
+    fn absorb_field_elements<F: FriField>(&mut self, label: &[u8], elements: &[F]) {
+        let words = elements
+            .iter()
+            .flat_map(field_element_words)
+            .collect::<Vec<FieldElement>>();
+        self.duplex(ABSORB_TAG, label, &words);
+    }
+
+    fn squeeze(&mut self, label: &[u8]) -> [u8; 32] {
+        self.duplex(SQUEEZE_TAG, label, &[]);
+        element_to_digest(self.state[0])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Poseidon2;
+
+impl Hasher for Poseidon2 {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        element_to_digest(poseidon2_sponge(&bytes_to_elements(data)))
+    }
+
+    // 2-to-1 compression of the field elements, an odd node is promoted as for the others
+    fn concat_and_hash(left: &[u8; 32], right: Option<&[u8; 32]>) -> [u8; 32] {
+        match right {
+            Some(right) => element_to_digest(poseidon2_compress(
+                digest_to_element(left),
+                digest_to_element(right),
+            )),
+            None => *left,
+        }
+    }
+}
+
+impl MerkleHasher for Poseidon2 {
+    const ID: u8 = 3;
+
+    type Sponge = Poseidon2Sponge;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::fri_code_layer::build_merkle_tree;
+
+    fn matrix_product(
+        matrix: [[u64; WIDTH]; WIDTH],
+        state: &[FieldElement; WIDTH],
+    ) -> [FieldElement; WIDTH] {
+        std::array::from_fn(|i| {
+            (0..WIDTH)
+                .map(|j| FieldElement::from(matrix[i][j]) * state[j])
+                .sum()
+        })
+    }
+
+    #[test]
+    fn test_poseidon2_linear_layers() {
+        let state = [
+            FieldElement::from(3u64),
+            FieldElement::from(5u64),
+            FieldElement::from(7u64),
+        ];
+
+        let mut external = state;
+        external_linear_layer(&mut external);
+        assert_eq!(
+            external,
+            matrix_product([[2, 1, 1], [1, 2, 1], [1, 1, 2]], &state)
+        );
+
+        let mut internal = state;
+        internal_linear_layer(&mut internal);
+        assert_eq!(
+            internal,
+            matrix_product([[2, 1, 1], [1, 2, 1], [1, 1, 3]], &state)
+        );
+    }
+
+    #[test]
+    fn test_poseidon2_known_answers() {
+        // Test vector of the reference implementation, big endian encodings
+        let mut state = [
+            FieldElement::ZERO,
+            FieldElement::ONE,
+            FieldElement::from(2u64),
+        ];
+        poseidon2_permutation(&mut state);
+        assert_eq!(
+            state,
+            [
+                "1b152349b1950b6a8ca75ee4407b6e26ca5cca5650534e56ef3fd45761fbf5f0",
+                "4c5793c87d51bdc2c08a32108437dc0000bd0275868f09ebc5f36919af5b3891",
+                "1fc8ed171e67902ca49863159fe5ba6325318843d13976143b8125f08b50dc6b",
+            ]
+            .map(from_hex)
+        );
+    }
+
+    #[test]
+    fn test_poseidon2_sponge() {
+        let elements = (1..=5)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        assert_ne!(
+            poseidon2_sponge(&elements),
+            poseidon2_sponge(&elements[..4])
+        );
+        // The length in the capacity separates the zero padding from a zero element
+        assert_ne!(
+            poseidon2_sponge(&elements),
+            poseidon2_sponge(&[elements.clone(), vec![FieldElement::ZERO]].concat())
+        );
+
+        let digest = Poseidon2::hash(b"abc");
+        assert!(FieldElement::from_bytes(&digest).is_some());
+        assert_ne!(
+            Poseidon2::concat_and_hash(&digest, Some(&Poseidon2::hash(b"d"))),
+            Poseidon2::concat_and_hash(&Poseidon2::hash(b"d"), Some(&digest))
+        );
+        assert_eq!(Poseidon2::concat_and_hash(&digest, None), digest);
+    }
+
+    #[test]
+    fn test_poseidon2_transcript_sponge() {
+        let element = FieldElement::from(7u64);
+
+        // A field element is a single word, as the digest encoding it
+        let mut sponge = Poseidon2Sponge::default();
+        sponge.absorb_field_elements(b"value", &[element]);
+        let mut expected = Poseidon2Sponge::default();
+        expected.duplex(ABSORB_TAG, b"value", &[element]);
+        assert_eq!(sponge, expected);
+
+        let mut digests_sponge = Poseidon2Sponge::default();
+        digests_sponge.absorb_digests(b"value", &[element_to_digest(element)]);
+        assert_eq!(digests_sponge, expected);
+
+        // Its bytes are another message
+        let mut bytes_sponge = Poseidon2Sponge::default();
+        bytes_sponge.absorb(b"value", &element.to_bytes());
+        assert_ne!(bytes_sponge, expected);
+
+        // An element of a 64 bits field is a single word too
+        let mut goldilocks_sponge = Poseidon2Sponge::default();
+        goldilocks_sponge.absorb_field_elements(b"value", &[Goldilocks::from(7u64)]);
+        assert_eq!(goldilocks_sponge, expected);
+
+        // The label and the squeezes update the state
+        let mut other_label = Poseidon2Sponge::default();
+        other_label.absorb_field_elements(b"other", &[element]);
+        assert_ne!(other_label, expected);
+        let challenge = sponge.squeeze(b"beta");
+        assert_eq!(challenge, element_to_digest(sponge.state[0]));
+        assert_ne!(sponge.squeeze(b"beta"), challenge);
+    }
+
+    #[test]
+    fn test_poseidon2_merkle_tree() {
+        let values = (1..=4)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let leaves = values
+            .iter()
+            .map(|value| digest_to_element(&Poseidon2::hash(&value.to_bytes())))
+            .collect::<Vec<FieldElement>>();
+
+        // The nodes are 2-to-1 compressions of the field elements of their children
+        let root = build_merkle_tree::<Poseidon2, FieldElement>(&values)
+            .root()
+            .unwrap();
+        assert_eq!(
+            root,
+            element_to_digest(poseidon2_compress(
+                poseidon2_compress(leaves[0], leaves[1]),
+                poseidon2_compress(leaves[2], leaves[3]),
+            ))
+        );
+    }
+}
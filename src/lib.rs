@@ -0,0 +1,21 @@
+pub mod batch_inversion;
+pub mod channel;
+pub mod domain;
+pub mod field;
+pub mod field_extension;
+pub mod field_provider_babybear;
+pub mod field_provider_goldilocks;
+pub mod field_provider_v1;
+pub mod fri_code_layer;
+pub mod fri_config;
+pub mod fri_proof;
+pub mod fri_verifier;
+pub mod hasher;
+pub mod hasher_blake3;
+pub mod hasher_keccak;
+pub mod hasher_poseidon2;
+pub mod merkle_cap;
+pub mod ntt;
+pub mod pcs;
+pub mod polynome;
+pub mod transcript;
@@ -1,14 +1,11 @@
-use channel::Channel;
-use ff::PrimeField;
-use field_provider_v1::FieldElement;
-use fri_code_layer::FriCodeLayer;
-use polynome::Polynome;
-use rs_merkle::{algorithms::Sha256, Hasher, MerkleProof};
-
-pub mod channel;
-pub mod field_provider_v1;
-pub mod fri_code_layer;
-pub mod polynome;
+use fri_basic_rustling::channel::{Channel, FRI_PROTOCOL_LABEL};
+use fri_basic_rustling::field::FriField;
+use fri_basic_rustling::field_provider_v1::FieldElement;
+use fri_basic_rustling::fri_code_layer::FriCodeLayer;
+use fri_basic_rustling::fri_config::FriConfig;
+use fri_basic_rustling::fri_proof::FriProof;
+use fri_basic_rustling::fri_verifier::FriVerifier;
+use fri_basic_rustling::polynome::Polynome;
 
 fn main() {
     let coefficients = vec![
@@ -21,34 +18,39 @@ fn main() {
         FieldElement::from(3u64),
     ];
     let poly = Polynome::new_poly(&coefficients);
-    let domain_size = 48; // 8 time degree of the polynome
-    let i_channel = &mut Channel::new();
+    let config = FriConfig::default();
+    let domain_size = config.domain_size(poly.degree().unwrap_or(0));
+    println!("Initial domain of size : {:?}", domain_size);
+    println!(
+        "Estimated security : {:.1} bits conjectured, {:.1} bits proven",
+        config.conjectured_security_bits(FieldElement::field_size_bits(), domain_size),
+        config.proven_security_bits(FieldElement::field_size_bits(), domain_size)
+    );
+    let i_channel = &mut Channel::<FieldElement>::new();
 
     println!("COMMITMENT PHASE");
-    let (_last_poly, fri_layers) = FriCodeLayer::fri_commit_phase(poly, domain_size, i_channel);
+    let (last_poly, fri_layers) =
+        FriCodeLayer::<FieldElement>::fri_commit_phase(poly, domain_size, &config, i_channel);
 
     println!("DECOMMITMENT PHASE");
-    let (decom, _queries) =
-        FriCodeLayer::fri_decommitment_phase(20, domain_size, &fri_layers, i_channel);
+    let (decom, queries) =
+        FriCodeLayer::fri_decommitment_phase(&config, domain_size, &fri_layers, i_channel);
 
-    decom.iter().for_each(|d| {
-        println!("MANAGE NEXT QUERY");
-        (0..d.layers_evaluations.len()).for_each(|i| {
-            println!("MANAGE NEXT LAYER");
-            let proof_hashes = d.layers_auth_paths[i].clone();
-            let proof = MerkleProof::<Sha256>::new(proof_hashes);
+    println!(
+        "Auth paths : {} hashes, {} with one auth path per query",
+        decom.auth_paths_len(),
+        FriCodeLayer::per_query_auth_paths_len(&config, &fri_layers, &queries)
+    );
 
-            
-            let eval_hash = Sha256::hash(
-                d.layers_evaluations[i]
-                    .to_repr()
-                    .as_ref()
-                    .try_into()
-                    .expect("Représentation incorrecte"),
-            );
+    let proof = FriProof::new(i_channel, &last_poly, decom, queries);
+    let proof_bytes = proof.to_bytes();
+    println!("Proof size : {:?} bytes", proof_bytes.len());
 
-            println!("Proof path for layer {:?}: {:?}", i, proof.proof_hashes_hex());
-            println!("Hash for layer {:?} : {:?}", i, hex::encode(eval_hash));
-        });
-    });
+    println!("VERIFICATION PHASE");
+    let received_proof =
+        FriProof::<FieldElement>::from_bytes(&proof_bytes).expect("Proof should decode");
+    let verifier =
+        FriVerifier::from_proof(domain_size, &config, FRI_PROTOCOL_LABEL, &received_proof);
+    let result = verifier.verify_proof(&received_proof);
+    println!("Verification result : {:?}", result);
 }
@@ -0,0 +1,277 @@
+// Subject: Merkle cap commitments
+//
+// A Merkle tree of 2^d leaves is committed by its cap of height k : the 2^k nodes at
+// depth k, each the root of a subtree of 2^(d-k) consecutive leaves. The root is the cap
+// of height 0. An auth path stops at the cap node of its subtree, k hashes shorter than
+// a path to the root, at the cost of the 2^k - 1 more hashes sent once in the cap.
+// The root of the tree built over the cap nodes is the root of the whole tree.
+
+use rs_merkle::{MerkleProof, MerkleTree};
+
+use crate::hasher::MerkleHasher;
+
+// Caps beyond 2^16 nodes outweigh any saving on the auth paths
+pub const MAX_MERKLE_CAP_HEIGHT: usize = 16;
+
+#[derive(Clone)]
+pub struct MerkleCapTree<H: MerkleHasher> {
+    // One tree per cap node, in the order of their leaves
+    subtrees: Vec<MerkleTree<H>>,
+    leaves_len: usize,
+}
+
+impl<H: MerkleHasher> MerkleCapTree<H> {
+    // The cap height is lowered to the height of the tree if needed, the number of leaves
+    // should be a power of two with a cap
+    pub fn new(leaves: &[[u8; 32]], cap_height: usize) -> Self {
+        let cap_height = cap_height.min(leaves.len().max(1).ilog2() as usize);
+        assert!(
+            cap_height == 0 || leaves.len().is_power_of_two(),
+            "A Merkle cap needs a power of two number of leaves"
+        );
+        let subtree_len = leaves.len() >> cap_height;
+        let subtrees = leaves
+            .chunks(subtree_len.max(1))
+            .map(MerkleTree::<H>::from_leaves)
+            .collect();
+
+        Self {
+            subtrees,
+            leaves_len: leaves.len(),
+        }
+    }
+
+    pub fn leaves_len(&self) -> usize {
+        self.leaves_len
+    }
+
+    pub fn cap(&self) -> Vec<[u8; 32]> {
+        self.subtrees
+            .iter()
+            .filter_map(|subtree| subtree.root())
+            .collect()
+    }
+
+    pub fn root(&self) -> Option<[u8; 32]> {
+        cap_root::<H>(&self.cap())
+    }
+
+    // Hex encoding of the cap nodes one after the other, the root hex without a cap
+    pub fn cap_hex(&self) -> Option<String> {
+        let cap = self.cap();
+        (!cap.is_empty()).then(|| hex::encode(cap.concat()))
+    }
+
+    // Multi-proofs of the sorted leaves up to the cap, subtree after subtree
+    pub fn proof(&self, leaf_indices: &[usize]) -> Vec<[u8; 32]> {
+        let subtree_len = self.leaves_len / self.subtrees.len();
+        self.subtrees
+            .iter()
+            .enumerate()
+            .flat_map(|(s, subtree)| {
+                let local_indices = leaf_indices
+                    .iter()
+                    .filter(|i| *i / subtree_len == s)
+                    .map(|i| i % subtree_len)
+                    .collect::<Vec<usize>>();
+                if local_indices.is_empty() {
+                    vec![]
+                } else {
+                    subtree.proof(&local_indices).proof_hashes().to_vec()
+                }
+            })
+            .collect()
+    }
+}
+
+// Root of the tree whose cap is given
+pub fn cap_root<H: MerkleHasher>(cap: &[[u8; 32]]) -> Option<[u8; 32]> {
+    MerkleTree::<H>::from_leaves(cap).root()
+}
+
+// Cap nodes from their hex encoding, None if it is not a power of two number of hashes
+pub fn decode_cap(cap_hex: &str) -> Option<Vec<[u8; 32]>> {
+    let bytes = hex::decode(cap_hex).ok()?;
+    if bytes.len() % 32 != 0 || !(bytes.len() / 32).is_power_of_two() {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks(32)
+            .map(|node| node.try_into().expect("Chunks of 32 bytes"))
+            .collect(),
+    )
+}
+
+// Number of hashes of the multi-proof of the sorted, distinct leaves in a tree of a power
+// of two number of leaves : at each level, the siblings which cannot be computed
+pub fn multi_proof_len(leaf_indices: &[usize], leaves_len: usize) -> usize {
+    let mut indices = leaf_indices.to_vec();
+    let mut level_len = leaves_len;
+    let mut len = 0;
+    while level_len > 1 {
+        len += indices
+            .iter()
+            .filter(|i| indices.binary_search(&(*i ^ 1)).is_err())
+            .count();
+        indices = indices.iter().map(|i| i / 2).collect();
+        indices.dedup();
+        level_len /= 2;
+    }
+    len
+}
+
+// Check the multi-proofs of `MerkleCapTree::proof` against the cap, the leaves are sorted
+// by distinct indices
+pub fn verify_cap_proof<H: MerkleHasher>(
+    cap: &[[u8; 32]],
+    leaves_len: usize,
+    leaf_indices: &[usize],
+    leaves: &[[u8; 32]],
+    proof: &[[u8; 32]],
+) -> bool {
+    if cap.is_empty() || !leaves_len.is_multiple_of(cap.len()) || leaf_indices.len() != leaves.len()
+    {
+        return false;
+    }
+    let subtree_len = leaves_len / cap.len();
+
+    let mut proof = proof;
+    for (s, cap_node) in cap.iter().enumerate() {
+        let (local_indices, local_leaves): (Vec<usize>, Vec<[u8; 32]>) = leaf_indices
+            .iter()
+            .zip(leaves)
+            .filter(|(i, _)| **i / subtree_len == s)
+            .map(|(i, leaf)| (i % subtree_len, *leaf))
+            .unzip();
+        if local_indices.is_empty() {
+            continue;
+        }
+
+        let len = multi_proof_len(&local_indices, subtree_len);
+        if proof.len() < len {
+            return false;
+        }
+        let (subtree_proof, rest) = proof.split_at(len);
+        proof = rest;
+        if !MerkleProof::<H>::new(subtree_proof.to_vec()).verify(
+            *cap_node,
+            &local_indices,
+            &local_leaves,
+            subtree_len,
+        ) {
+            return false;
+        }
+    }
+    proof.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rs_merkle::Hasher;
+
+    use super::*;
+    use crate::hasher::Sha256;
+
+    fn leaves(len: usize) -> Vec<[u8; 32]> {
+        (0..len as u64)
+            .map(|i| Sha256::hash(&i.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn test_cap_root() {
+        let leaves = leaves(16);
+        let root = MerkleTree::<Sha256>::from_leaves(&leaves).root().unwrap();
+
+        for cap_height in 0..=4 {
+            let tree = MerkleCapTree::<Sha256>::new(&leaves, cap_height);
+            assert_eq!(tree.cap().len(), 1 << cap_height);
+            assert_eq!(tree.root(), Some(root));
+            assert_eq!(decode_cap(&tree.cap_hex().unwrap()), Some(tree.cap()));
+        }
+
+        // The cap height is at most the tree height
+        assert_eq!(MerkleCapTree::<Sha256>::new(&leaves, 6).cap(), leaves);
+        assert_eq!(
+            MerkleCapTree::<Sha256>::new(&leaves, 0).cap_hex(),
+            Some(hex::encode(root))
+        );
+        assert_eq!(decode_cap("00"), None);
+    }
+
+    #[test]
+    fn test_multi_proof_len() {
+        let leaves = leaves(32);
+        let tree = MerkleTree::<Sha256>::from_leaves(&leaves);
+        for indices in [
+            vec![0],
+            vec![0, 1],
+            vec![3, 17],
+            vec![2, 5, 6, 7, 30],
+            vec![],
+        ] {
+            assert_eq!(
+                multi_proof_len(&indices, leaves.len()),
+                tree.proof(&indices).proof_hashes().len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_cap_proof() {
+        let leaves = leaves(32);
+        let indices = vec![1, 4, 5, 20, 31];
+        let opened = indices
+            .iter()
+            .map(|i| leaves[*i])
+            .collect::<Vec<[u8; 32]>>();
+
+        let full_proof_len = MerkleCapTree::<Sha256>::new(&leaves, 0)
+            .proof(&indices)
+            .len();
+        for cap_height in 0..=5 {
+            let tree = MerkleCapTree::<Sha256>::new(&leaves, cap_height);
+            let cap = tree.cap();
+            let proof = tree.proof(&indices);
+            assert!(verify_cap_proof::<Sha256>(
+                &cap, 32, &indices, &opened, &proof
+            ));
+            assert!(proof.len() <= full_proof_len);
+
+            // Tampered leaf, cap node or extra hash
+            let mut tampered = opened.clone();
+            tampered[2][0] ^= 1;
+            assert!(!verify_cap_proof::<Sha256>(
+                &cap, 32, &indices, &tampered, &proof
+            ));
+            let mut tampered_cap = cap.clone();
+            tampered_cap[cap.len() - 1][0] ^= 1;
+            assert!(!verify_cap_proof::<Sha256>(
+                &tampered_cap,
+                32,
+                &indices,
+                &opened,
+                &proof
+            ));
+            let longer_proof = [proof.clone(), vec![[0u8; 32]]].concat();
+            assert!(!verify_cap_proof::<Sha256>(
+                &cap,
+                32,
+                &indices,
+                &opened,
+                &longer_proof
+            ));
+        }
+
+        // A single query path shortens by the cap height
+        let single_path_len = |cap_height| {
+            MerkleCapTree::<Sha256>::new(&leaves, cap_height)
+                .proof(&[9])
+                .len()
+        };
+        assert_eq!(single_path_len(0), 5);
+        assert_eq!(single_path_len(3), 2);
+    }
+}
@@ -0,0 +1,155 @@
+// Subject: Radix-2 number-theoretic transform over the field
+//
+// The transforms work in place on slices whose length is a power of two.
+// `root` must be a primitive root of unity of order the slice length.
+// Values are read and written in natural order. They may live in an extension of the
+// field of the roots, the butterflies only multiply them by base field twiddles.
+
+use crate::field::{ExtensionField, FriField};
+
+fn bit_reverse_permutation<E>(values: &mut [E]) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+// Coefficients -> evaluations on { root^i }
+pub fn ntt<F: FriField, E: ExtensionField<F>>(values: &mut [E], root: &F) {
+    let n = values.len();
+    assert!(n.is_power_of_two(), "NTT size should be a power of two");
+
+    bit_reverse_permutation(values);
+
+    // Cooley-Tukey butterflies, len is the size of the sub transforms being merged
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let w_len = root.pow([(n / len) as u64]);
+
+        let mut twiddles = Vec::with_capacity(half);
+        let mut w = F::ONE;
+        for _ in 0..half {
+            twiddles.push(w);
+            w *= w_len;
+        }
+
+        for start in (0..n).step_by(len) {
+            for j in 0..half {
+                let u = values[start + j];
+                let v = values[start + j + half] * twiddles[j];
+                values[start + j] = u + v;
+                values[start + j + half] = u - v;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+// Evaluations on { root^i } -> coefficients
+pub fn inverse_ntt<F: FriField, E: ExtensionField<F>>(values: &mut [E], root: &F) {
+    let root_inv = root.invert().expect("Root of unity should be invertible");
+    ntt(values, &root_inv);
+
+    let n_inv = F::from(values.len() as u64)
+        .invert()
+        .expect("NTT size should be invertible in the field");
+    values.iter_mut().for_each(|v| *v *= n_inv);
+}
+
+// Coefficients -> evaluations on { shift * root^i }
+pub fn coset_ntt<F: FriField, E: ExtensionField<F>>(values: &mut [E], root: &F, shift: &F) {
+    scale_by_powers(values, shift);
+    ntt(values, root);
+}
+
+// Evaluations on { shift * root^i } -> coefficients
+pub fn inverse_coset_ntt<F: FriField, E: ExtensionField<F>>(values: &mut [E], root: &F, shift: &F) {
+    inverse_ntt(values, root);
+    let shift_inv = shift.invert().expect("Coset shift should not be zero");
+    scale_by_powers(values, &shift_inv);
+}
+
+// values[i] *= factor^i
+fn scale_by_powers<F: FriField, E: ExtensionField<F>>(values: &mut [E], factor: &F) {
+    let mut power = F::ONE;
+    for v in values.iter_mut() {
+        *v *= power;
+        power *= factor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::domain::two_adic_root_of_unity;
+    use crate::field_provider_v1::FieldElement;
+    use crate::polynome::Polynome;
+    use ff::Field;
+    use ff::PrimeField;
+
+    fn sample_coefficients(n: u64) -> Vec<FieldElement> {
+        (0..n).map(|i| FieldElement::from(3 * i * i + 7)).collect()
+    }
+
+    #[test]
+    fn test_ntt_matches_naive_evaluation() {
+        let coefficients = sample_coefficients(16);
+        let root: FieldElement = two_adic_root_of_unity(4);
+        let poly = Polynome::new_poly(&coefficients);
+
+        let mut values = coefficients.clone();
+        ntt(&mut values, &root);
+
+        (0..16).for_each(|i| assert_eq!(values[i], poly.evaluate(&root.pow([i as u64]))));
+    }
+
+    #[test]
+    fn test_inverse_ntt() {
+        let coefficients = sample_coefficients(32);
+        let root: FieldElement = two_adic_root_of_unity(5);
+
+        let mut values = coefficients.clone();
+        ntt(&mut values, &root);
+        inverse_ntt(&mut values, &root);
+
+        assert_eq!(values, coefficients);
+    }
+
+    #[test]
+    fn test_coset_ntt() {
+        let coefficients = sample_coefficients(8);
+        let root: FieldElement = two_adic_root_of_unity(3);
+        let shift = FieldElement::MULTIPLICATIVE_GENERATOR;
+        let poly = Polynome::new_poly(&coefficients);
+
+        let mut values = coefficients.clone();
+        coset_ntt(&mut values, &root, &shift);
+        (0..8).for_each(|i| assert_eq!(values[i], poly.evaluate(&(shift * root.pow([i as u64])))));
+
+        inverse_coset_ntt(&mut values, &root, &shift);
+        assert_eq!(values, coefficients);
+    }
+
+    #[test]
+    fn test_ntt_size_one() {
+        let mut values = vec![FieldElement::from(5u64)];
+        ntt(&mut values, &FieldElement::ONE);
+        assert_eq!(values, vec![FieldElement::from(5u64)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "NTT size should be a power of two")]
+    fn test_ntt_non_power_of_two() {
+        let mut values = sample_coefficients(6);
+        ntt(&mut values, &FieldElement::ONE);
+    }
+}
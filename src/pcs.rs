@@ -0,0 +1,337 @@
+// Subject: FRI as a polynomial commitment scheme
+//
+// The commitment to f is the Merkle root of its evaluations on the enlarged domain, which
+// is the first FRI layer. Opening f at z to v runs FRI on the quotient (f(x) - v) / (x - z)
+// from this same layer : the quotient is a polynomial of degree deg(f) - 1 only if
+// f(z) = v, and the verifier recomputes it at the queried points from the openings of f.
+// The quotient is degree-corrected up to the degree bound of the domain, so that FRI
+// tests the exact bound max_degree - 1 of the quotient whatever the domain size.
+// The opening point should be out of the evaluation domain, which holds for a point of
+// the extension or sampled at random, a point of the domain is an error.
+
+use std::marker::PhantomData;
+
+use crate::channel::{Channel, FRI_PROTOCOL_LABEL};
+use crate::field::{ExtensionField, TwoAdicField};
+use crate::fri_code_layer::{
+    build_layer_merkle_tree, evaluate_on_enlarged_domain, generate_enlarged_evaluation_domain,
+    FriCodeLayer, QuotientError,
+};
+use crate::fri_config::FriConfig;
+use crate::fri_proof::FriProof;
+use crate::fri_verifier::{FriRejectionReason, FriVerificationResult, FriVerifier};
+use crate::hasher::{MerkleHasher, Sha256};
+use crate::merkle_cap::cap_root;
+use crate::polynome::Polynome;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    // Merkle root of the evaluations on the enlarged domain
+    pub root: [u8; 32],
+}
+
+// Commitments to polynomials over F of degree at most max_degree, opened at points of E,
+// with Merkle trees hashed with H
+#[derive(Clone, Debug)]
+pub struct FriPcs<F: TwoAdicField, E: ExtensionField<F> = F, H: MerkleHasher = Sha256> {
+    config: FriConfig,
+    max_degree: usize,
+    domain_size: usize,
+    label: Vec<u8>,
+    fields: PhantomData<(F, E, H)>,
+}
+
+impl<F: TwoAdicField, E: ExtensionField<F>, H: MerkleHasher> FriPcs<F, E, H> {
+    pub fn new(config: &FriConfig, max_degree: usize) -> Self {
+        Self::new_with_label(config, max_degree, FRI_PROTOCOL_LABEL)
+    }
+
+    // The DEEP mode of the configuration is not used, the opening point plays its role
+    // The commitment is not hiding, the zero-knowledge mode is not supported
+    pub fn new_with_label(config: &FriConfig, max_degree: usize, label: &[u8]) -> Self {
+        config.validate().expect("Invalid FRI configuration");
+        assert!(
+            !config.zero_knowledge,
+            "The polynomial commitment scheme has no zero-knowledge mode"
+        );
+        Self {
+            config: FriConfig {
+                deep: false,
+                ..*config
+            },
+            max_degree,
+            domain_size: config.domain_size(max_degree),
+            label: label.to_vec(),
+            fields: PhantomData,
+        }
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    pub fn commit(&self, poly: &Polynome<F>) -> Commitment {
+        let domain = generate_enlarged_evaluation_domain(self.domain_size);
+        let evaluations = evaluate_on_enlarged_domain(poly, &domain);
+        let root = build_layer_merkle_tree::<H, F>(&evaluations, 1, self.config.leaf_coset_size())
+            .root()
+            .expect("The domain is not empty");
+        Commitment { root }
+    }
+
+    // Value of the polynomial at the point and the proof of this value,
+    // `QuotientError::DenominatorVanishes(i)` if the point is the domain element i
+    pub fn open(
+        &self,
+        poly: &Polynome<F>,
+        point: &E,
+    ) -> Result<(E, FriProof<F, E, H>), QuotientError> {
+        let value = poly.to_extension::<E>().evaluate(point);
+
+        let domain = generate_enlarged_evaluation_domain(self.domain_size);
+        let mut channel = Channel::new_with_label(&self.label);
+        let (last_poly, fri_layers) = FriCodeLayer::<F, E, H>::fri_commit_opening(
+            evaluate_on_enlarged_domain(poly, &domain),
+            &domain,
+            point,
+            &value,
+            self.max_degree,
+            &self.config,
+            &mut channel,
+        )?;
+        let (decommitments, queries) = FriCodeLayer::fri_decommitment_phase(
+            &self.config,
+            self.domain_size,
+            &fri_layers,
+            &mut channel,
+        );
+
+        Ok((
+            value,
+            FriProof::new(&channel, &last_poly, decommitments, queries),
+        ))
+    }
+
+    pub fn verify(
+        &self,
+        commitment: &Commitment,
+        point: &E,
+        value: &E,
+        proof: &FriProof<F, E, H>,
+    ) -> FriVerificationResult {
+        // The first layer cap hashes up to the committed root
+        if proof.layers_caps.first().and_then(|cap| cap_root::<H>(cap)) != Some(commitment.root) {
+            return FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::CommitmentMismatch,
+            };
+        }
+
+        FriVerifier::for_opening(
+            self.domain_size,
+            &self.config,
+            &self.label,
+            proof,
+            point,
+            value,
+            self.max_degree,
+        )
+        .verify_proof(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_extension::QuadraticExtension;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+    use ff::Field;
+
+    #[test]
+    fn test_commit_open_verify() {
+        let poly = Polynome::new_poly(
+            &(1..=13)
+                .map(|i| FieldElement::from(i * 3))
+                .collect::<Vec<FieldElement>>(),
+        );
+        let pcs = FriPcs::<FieldElement>::new(&FriConfig::default(), poly.degree().unwrap());
+        let commitment = pcs.commit(&poly);
+
+        let point = FieldElement::from(123456789u64);
+        let (value, proof) = pcs.open(&poly, &point).unwrap();
+        assert_eq!(value, poly.evaluate(&point));
+        assert!(pcs
+            .verify(&commitment, &point, &value, &proof)
+            .is_accepted());
+
+        // The proof is bound to the opening
+        assert!(!pcs
+            .verify(&commitment, &point, &(value + FieldElement::ONE), &proof)
+            .is_accepted());
+        assert!(!pcs
+            .verify(&commitment, &(point + FieldElement::ONE), &value, &proof)
+            .is_accepted());
+
+        let other_commitment = pcs.commit(&Polynome::new_poly(&[FieldElement::ONE]));
+        assert_eq!(
+            pcs.verify(&other_commitment, &point, &value, &proof),
+            FriVerificationResult::Reject {
+                query: 0,
+                layer: 0,
+                reason: FriRejectionReason::CommitmentMismatch,
+            }
+        );
+    }
+
+    #[test]
+    fn test_open_at_extension_point() {
+        type E = QuadraticExtension<Goldilocks>;
+        let poly = Polynome::new_poly(
+            &(1..=20)
+                .map(|i| Goldilocks::from(i * i))
+                .collect::<Vec<Goldilocks>>(),
+        );
+        let config = FriConfig {
+            folding_factor: 4,
+            ..FriConfig::default()
+        };
+        let pcs = FriPcs::<Goldilocks, E>::new(&config, poly.degree().unwrap());
+        let commitment = pcs.commit(&poly);
+
+        let point = E::x() + E::from(7u64);
+        let (value, proof) = pcs.open(&poly, &point).unwrap();
+
+        // The opening proof goes through the binary encoding
+        let received_proof = FriProof::<Goldilocks, E>::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(pcs
+            .verify(&commitment, &point, &value, &received_proof)
+            .is_accepted());
+        assert!(!pcs
+            .verify(&commitment, &point, &(value + E::ONE), &received_proof)
+            .is_accepted());
+    }
+
+    #[test]
+    fn test_commit_with_coset_leaves() {
+        let poly = Polynome::new_poly(
+            &(1..=9)
+                .map(FieldElement::from)
+                .collect::<Vec<FieldElement>>(),
+        );
+        let config = FriConfig {
+            coset_leaves: true,
+            ..FriConfig::default()
+        };
+        let pcs = FriPcs::<FieldElement>::new(&config, poly.degree().unwrap());
+        let commitment = pcs.commit(&poly);
+        assert_ne!(
+            commitment,
+            FriPcs::<FieldElement>::new(&FriConfig::default(), poly.degree().unwrap())
+                .commit(&poly)
+        );
+
+        let point = FieldElement::from(987654321u64);
+        let (value, proof) = pcs.open(&poly, &point).unwrap();
+        assert!(pcs
+            .verify(&commitment, &point, &value, &proof)
+            .is_accepted());
+    }
+
+    #[test]
+    fn test_open_with_merkle_cap() {
+        let poly = Polynome::new_poly(
+            &(1..=9)
+                .map(FieldElement::from)
+                .collect::<Vec<FieldElement>>(),
+        );
+        let config = FriConfig {
+            merkle_cap_height: 2,
+            ..FriConfig::default()
+        };
+        // The commitment is still the root, the first cap of the proof hashes up to it
+        let pcs = FriPcs::<FieldElement>::new(&config, poly.degree().unwrap());
+        let commitment = pcs.commit(&poly);
+        assert_eq!(
+            commitment,
+            FriPcs::<FieldElement>::new(&FriConfig::default(), poly.degree().unwrap())
+                .commit(&poly)
+        );
+
+        let point = FieldElement::from(987654321u64);
+        let (value, proof) = pcs.open(&poly, &point).unwrap();
+        assert_eq!(proof.layers_caps[0].len(), 4);
+        assert!(pcs
+            .verify(&commitment, &point, &value, &proof)
+            .is_accepted());
+    }
+
+    #[test]
+    #[should_panic(expected = "The polynomial commitment scheme has no zero-knowledge mode")]
+    fn test_no_zero_knowledge() {
+        let config = FriConfig {
+            zero_knowledge: true,
+            ..FriConfig::default()
+        };
+        FriPcs::<FieldElement>::new(&config, 7);
+    }
+
+    #[test]
+    fn test_reject_degree_too_large() {
+        // Commitments to polynomials of degree at most 7
+        let pcs = FriPcs::<FieldElement>::new(&FriConfig::default(), 7);
+
+        // Degree 31, and degree 8 whose codeword still fits the degree bound 8 of the domain
+        for len in [32, 9] {
+            let poly = Polynome::new_poly(
+                &(1..=len)
+                    .map(FieldElement::from)
+                    .collect::<Vec<FieldElement>>(),
+            );
+            let commitment = pcs.commit(&poly);
+
+            let point = FieldElement::from(42u64);
+            let (value, proof) = pcs.open(&poly, &point).unwrap();
+            assert!(!pcs
+                .verify(&commitment, &point, &value, &proof)
+                .is_accepted());
+        }
+
+        // Degree 5 below a degree bound of 6 on the same domain size
+        let pcs = FriPcs::<FieldElement>::new(&FriConfig::default(), 6);
+        let poly = Polynome::new_poly(
+            &(1..=7)
+                .map(FieldElement::from)
+                .collect::<Vec<FieldElement>>(),
+        );
+        let point = FieldElement::from(42u64);
+        let (value, proof) = pcs.open(&poly, &point).unwrap();
+        assert!(pcs
+            .verify(&pcs.commit(&poly), &point, &value, &proof)
+            .is_accepted());
+        let pcs_5 = FriPcs::<FieldElement>::new(&FriConfig::default(), 5);
+        assert_eq!(pcs_5.domain_size(), pcs.domain_size());
+        let (value, proof) = pcs_5.open(&poly, &point).unwrap();
+        assert!(!pcs_5
+            .verify(&pcs_5.commit(&poly), &point, &value, &proof)
+            .is_accepted());
+    }
+
+    #[test]
+    fn test_open_in_domain() {
+        let poly = Polynome::new_poly(
+            &(1..=8)
+                .map(FieldElement::from)
+                .collect::<Vec<FieldElement>>(),
+        );
+        let pcs = FriPcs::<FieldElement>::new(&FriConfig::default(), poly.degree().unwrap());
+        let domain = generate_enlarged_evaluation_domain::<FieldElement>(pcs.domain_size());
+
+        assert_eq!(
+            pcs.open(&poly, &domain[3]).err(),
+            Some(QuotientError::DenominatorVanishes(3))
+        );
+    }
+}
@@ -1,4 +1,13 @@
-use crate::field_provider_v1::FieldElement;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::batch_inversion::{batch_inverse, ZeroPolicy};
+use crate::domain::Domain;
+use crate::field::{ExtensionField, FriField, TwoAdicField};
+use crate::ntt::{coset_ntt, inverse_coset_ntt};
+
+// Below this number of coefficients of the smaller operand, schoolbook multiplication
+// is faster than the three NTT
+const NTT_MUL_THRESHOLD: usize = 32;
 
 /// Représentation de polynôme (une indéterminée - représentation de polynôme univarié)
 #[derive(Clone, Debug)]
@@ -6,38 +15,50 @@ pub struct Polynome<T> {
     pub coefficients: Vec<T>,
 }
 
-fn remove_zeroes(coeffs: &[FieldElement]) -> Vec<FieldElement> {
+fn remove_zeroes<F: FriField>(coeffs: &[F]) -> Vec<F> {
     let mut no_zeroes_coefficients = coeffs
         .iter()
         .rev()
-        .skip_while(|x| **x == FieldElement::from(0u64))
+        .skip_while(|x| **x == F::ZERO)
         .cloned()
-        .collect::<Vec<FieldElement>>();
+        .collect::<Vec<F>>();
     no_zeroes_coefficients.reverse();
     no_zeroes_coefficients
 }
 
 // Pad with zero coefficients to length n
 // Truncate if new length is less than size
-fn pad_with_zero_coefficients_to_length(pa: &mut Polynome<FieldElement>, n: usize) {
-    pa.coefficients.resize(n, FieldElement::from(0u64));
+fn pad_with_zero_coefficients_to_length<F: FriField>(pa: &mut Polynome<F>, n: usize) {
+    pa.coefficients.resize(n, F::ZERO);
+}
+
+// Quotient of the polynomial by (x - a), the remainder is dropped
+fn divide_by_linear<F: FriField>(coeffs: &[F], a: &F) -> Polynome<F> {
+    let mut quotient = vec![F::ZERO; coeffs.len().saturating_sub(1)];
+    let mut carry = F::ZERO;
+    for i in (0..quotient.len()).rev() {
+        carry = coeffs[i + 1] + carry * a;
+        quotient[i] = carry;
+    }
+    Polynome::new_poly(&quotient)
 }
 
-impl Polynome<FieldElement> {
+impl<F: FriField> Polynome<F> {
     // Constructeur avec coefficients
-    pub fn new_poly(coefficients: &[FieldElement]) -> Self {
+    pub fn new_poly(coefficients: &[F]) -> Self {
         Polynome {
             coefficients: remove_zeroes(coefficients),
         }
     }
 
-    pub fn degree(&self) -> usize {
-        self.coefficients.len() - 1
+    // None for the zero polynomial, which has no coefficient
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
     }
-    
-    pub fn evaluate(&self, x: &FieldElement) -> FieldElement {
-        let mut result = FieldElement::from(0u64);
-        let mut power = FieldElement::from(1u64);
+
+    pub fn evaluate(&self, x: &F) -> F {
+        let mut result = F::ZERO;
+        let mut power = F::ONE;
         for coefficient in &self.coefficients {
             result += power * coefficient;
             power *= x;
@@ -45,15 +66,121 @@ impl Polynome<FieldElement> {
         result
     }
 
-    pub fn evaluate_sliding(&self, input: &[FieldElement]) -> Vec<FieldElement> {
+    pub fn evaluate_sliding(&self, input: &[F]) -> Vec<F> {
         input.iter().map(|x| self.evaluate(x)).collect()
     }
 
+    // Evaluate on every point of a two-adic domain with a coset NTT, O(n log n)
+    // Coefficients beyond the domain size are wrapped as x^(q*n + r) = shift^(q*n) * x^r on the domain
+    // The coefficients may live in an extension of the field of the domain
+    pub fn evaluate_over_domain<B: TwoAdicField>(&self, domain: &Domain<B>) -> Vec<F>
+    where
+        F: ExtensionField<B>,
+    {
+        let n = domain.size();
+        let shift_pow_n = domain.shift().pow([n as u64]);
+
+        let mut values = vec![F::ZERO; n];
+        let mut factor = B::ONE;
+        for chunk in self.coefficients.chunks(n) {
+            for (v, c) in values.iter_mut().zip(chunk) {
+                *v += *c * factor;
+            }
+            factor *= shift_pow_n;
+        }
+
+        coset_ntt(&mut values, &domain.generator(), &domain.shift());
+        values
+    }
+
+    // Unique polynomial of degree < domain size taking the given values on the domain
+    pub fn interpolate_from_domain<B: TwoAdicField>(
+        evaluations: &[F],
+        domain: &Domain<B>,
+    ) -> Polynome<F>
+    where
+        F: ExtensionField<B>,
+    {
+        assert_eq!(
+            evaluations.len(),
+            domain.size(),
+            "One evaluation per domain element is expected"
+        );
+        let mut coefficients = evaluations.to_vec();
+        inverse_coset_ntt(&mut coefficients, &domain.generator(), &domain.shift());
+        Polynome::new_poly(&coefficients)
+    }
+
+    // Product of (x - p) over the points, zero on every point
+    pub fn vanishing_polynomial(points: &[F]) -> Polynome<F> {
+        let mut coefficients = vec![F::ONE];
+        for p in points {
+            // (c_0 + c_1 x + ...) * (x - p)
+            coefficients.insert(0, F::ZERO);
+            for i in 0..coefficients.len() - 1 {
+                let next = coefficients[i + 1];
+                coefficients[i] -= next * p;
+            }
+        }
+        Polynome::new_poly(&coefficients)
+    }
+
+    // x^n - c, zero on the coset shift * <g> of size n when c = shift^n
+    pub fn subgroup_vanishing_polynomial(n: usize, c: &F) -> Polynome<F> {
+        let mut coefficients = vec![F::ZERO; n + 1];
+        coefficients[0] = -*c;
+        coefficients[n] = F::ONE;
+        Polynome::new_poly(&coefficients)
+    }
+
+    // L_i of degree n - 1, one on points[i] and zero on the other points
+    pub fn lagrange_basis(points: &[F], i: usize) -> Polynome<F> {
+        let vanishing = Polynome::vanishing_polynomial(points);
+        let numerator = divide_by_linear(&vanishing.coefficients, &points[i]);
+        let denominator = numerator.evaluate(&points[i]);
+        let denominator_inv = Option::<F>::from(denominator.invert())
+            .expect("Interpolation points should be distinct");
+        numerator.scale(&denominator_inv)
+    }
+
+    // Unique polynomial of degree < n taking the values on n distinct points, O(n^2)
+    // sum_i values[i] * w_i * Z(x) / (x - points[i]) with 1 / w_i = prod_(j != i) (points[i] - points[j])
+    pub fn interpolate(points: &[F], values: &[F]) -> Polynome<F> {
+        assert_eq!(
+            points.len(),
+            values.len(),
+            "One value per interpolation point is expected"
+        );
+        let vanishing = Polynome::vanishing_polynomial(points);
+        let numerators = points
+            .iter()
+            .map(|p| divide_by_linear(&vanishing.coefficients, p))
+            .collect::<Vec<Polynome<F>>>();
+
+        // Z(x) / (x - points[i]) at points[i] is the product of the differences
+        let denominators = numerators
+            .iter()
+            .zip(points)
+            .map(|(n, p)| n.evaluate(p))
+            .collect::<Vec<F>>();
+        let weights = batch_inverse(&denominators, ZeroPolicy::Error)
+            .expect("Interpolation points should be distinct");
+
+        let mut coefficients = vec![F::ZERO; points.len()];
+        for ((numerator, w), v) in numerators.iter().zip(weights).zip(values) {
+            let factor = w * v;
+            for (c, n) in coefficients.iter_mut().zip(&numerator.coefficients) {
+                *c += *n * factor;
+            }
+        }
+        Polynome::new_poly(&coefficients)
+    }
+
     /// Pads polynomial representations with minimum number of zeros to match lengths.
     pub fn pad_with_zero_coefficients(
-        pa: &Polynome<FieldElement>,
-        pb: &Polynome<FieldElement>,
-    ) -> (Polynome<FieldElement>, Polynome<FieldElement>) {
+        pa: &Polynome<F>,
+        pb: &Polynome<F>,
+    ) -> (Polynome<F>, Polynome<F>) {
         let mut pa = pa.clone();
         let mut pb = pb.clone();
 
@@ -65,37 +192,221 @@ impl Polynome<FieldElement> {
         (pa, pb)
     }
 
-    pub fn fold_with_beta(&self, beta: &FieldElement) -> Polynome<FieldElement> {
-        let coefs = self.coefficients.clone();
+    // Same polynomial with its coefficients seen in an extension
+    pub fn to_extension<E: ExtensionField<F>>(&self) -> Polynome<E> {
+        Polynome {
+            coefficients: self.coefficients.iter().map(|c| E::from_base(*c)).collect(),
+        }
+    }
 
-        let even_coefs = coefs
-            .iter()
-            .step_by(2)
-            .cloned()
-            .collect::<Vec<FieldElement>>();
+    // Binary folding : f(x) = f_e(x^2) + x * f_o(x^2) folds into f_e + beta * f_o
+    // beta may live in an extension of the field of the coefficients,
+    // the folded polynomial then lives in the extension
+    pub fn fold_with_beta<E: ExtensionField<F>>(&self, beta: &E) -> Polynome<E> {
+        self.fold_with_factor(beta, 2)
+    }
+
+    // f(x) = sum_j x^j * f_j(x^k) for 0 <= j < k folds into sum_j beta^j * f_j
+    // The degree is divided by the folding factor k
+    pub fn fold_with_factor<E: ExtensionField<F>>(
+        &self,
+        beta: &E,
+        folding_factor: usize,
+    ) -> Polynome<E> {
+        assert!(
+            folding_factor >= 2 && folding_factor.is_power_of_two(),
+            "Folding factor should be a power of two"
+        );
+
+        // The coefficient m of the folded polynomial is sum_j beta^j * c_(m*k + j)
+        let coefficients = self
+            .coefficients
+            .chunks(folding_factor)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .rev()
+                    .fold(E::ZERO, |acc, c| acc * beta + E::from_base(*c))
+            })
+            .collect::<Vec<E>>();
+
+        Polynome::new_poly(&coefficients)
+    }
 
-        // Odd coefficients multiplied by beta (soc broken !!! - to refactor)
-        let odd_coefs_betarized = coefs
+    // The zero polynomial has no coefficient
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    // Product of every coefficient by the scalar
+    pub fn scale(&self, scalar: &F) -> Polynome<F> {
+        Polynome::new_poly(
+            &self
+                .coefficients
+                .iter()
+                .map(|c| *c * scalar)
+                .collect::<Vec<F>>(),
+        )
+    }
+
+    // O(n * m) product, for any field
+    pub fn mul_schoolbook(&self, other: &Polynome<F>) -> Polynome<F> {
+        if self.is_zero() || other.is_zero() {
+            return Polynome::new_poly(&[]);
+        }
+        let mut coefficients =
+            vec![F::ZERO; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] += *a * b;
+            }
+        }
+        Polynome::new_poly(&coefficients)
+    }
+
+    // O(n log n) product : pointwise product of the evaluations on a two-adic subgroup
+    // large enough to hold the product, the coefficients may live in an extension
+    pub fn mul_ntt<B: TwoAdicField>(&self, other: &Polynome<F>) -> Polynome<F>
+    where
+        F: ExtensionField<B>,
+    {
+        if self.is_zero() || other.is_zero() {
+            return Polynome::new_poly(&[]);
+        }
+        let size = (self.coefficients.len() + other.coefficients.len() - 1).next_power_of_two();
+        let domain = Domain::<B>::new(size);
+
+        let evaluations = self
+            .evaluate_over_domain(&domain)
             .iter()
-            .skip(1)
-            .step_by(2)
-            .map(|x| x.clone() * beta)
-            .collect::<Vec<FieldElement>>();
+            .zip(other.evaluate_over_domain(&domain))
+            .map(|(a, b)| *a * b)
+            .collect::<Vec<F>>();
+        Polynome::interpolate_from_domain(&evaluations, &domain)
+    }
 
-        let (even_poly, odd_poly) = Polynome::pad_with_zero_coefficients(
-            &Polynome::new_poly(&even_coefs),
-            &Polynome::new_poly(&odd_coefs_betarized),
-        );
+    // Long division : self = quotient * divisor + remainder with deg(remainder) < deg(divisor)
+    pub fn div_rem(&self, divisor: &Polynome<F>) -> (Polynome<F>, Polynome<F>) {
+        assert!(!divisor.is_zero(), "Division by the zero polynomial");
 
-        let mut new_coefs = vec![];
-        for (i, coef) in even_poly.coefficients.iter().enumerate() {
-            new_coefs.push(coef.clone());
-            if i < odd_poly.coefficients.len() {
-                new_coefs[i] += odd_poly.coefficients[i].clone();
+        let divisor_degree = divisor.degree().expect("The divisor is not zero");
+        if self.coefficients.len() <= divisor_degree {
+            return (Polynome::new_poly(&[]), self.clone());
+        }
+
+        let leading_inv = divisor.coefficients[divisor_degree]
+            .invert()
+            .expect("The leading coefficient is not zero");
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![F::ZERO; self.coefficients.len() - divisor_degree];
+
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + divisor_degree] * leading_inv;
+            quotient[i] = q;
+            for (j, d) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] -= q * d;
             }
         }
+        remainder.truncate(divisor_degree);
+
+        (
+            Polynome::new_poly(&quotient),
+            Polynome::new_poly(&remainder),
+        )
+    }
+}
+
+impl<F: FriField> Add<&Polynome<F>> for &Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn add(self, other: &Polynome<F>) -> Polynome<F> {
+        let (pa, pb) = Polynome::pad_with_zero_coefficients(self, other);
+        Polynome::new_poly(
+            &pa.coefficients
+                .iter()
+                .zip(&pb.coefficients)
+                .map(|(a, b)| *a + b)
+                .collect::<Vec<F>>(),
+        )
+    }
+}
+
+impl<F: FriField> Add for Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn add(self, other: Polynome<F>) -> Polynome<F> {
+        &self + &other
+    }
+}
+
+impl<F: FriField> Sub<&Polynome<F>> for &Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn sub(self, other: &Polynome<F>) -> Polynome<F> {
+        self + &(-other)
+    }
+}
+
+impl<F: FriField> Sub for Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn sub(self, other: Polynome<F>) -> Polynome<F> {
+        &self - &other
+    }
+}
+
+impl<F: FriField> Neg for &Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn neg(self) -> Polynome<F> {
+        Polynome {
+            coefficients: self.coefficients.iter().map(|c| -*c).collect(),
+        }
+    }
+}
 
-        return Polynome::new_poly(&new_coefs);
+impl<F: FriField> Neg for Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn neg(self) -> Polynome<F> {
+        -&self
+    }
+}
+
+// Schoolbook for small operands, NTT over the field itself for large ones
+impl<F: TwoAdicField> Mul<&Polynome<F>> for &Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn mul(self, other: &Polynome<F>) -> Polynome<F> {
+        if self.coefficients.len().min(other.coefficients.len()) < NTT_MUL_THRESHOLD {
+            self.mul_schoolbook(other)
+        } else {
+            self.mul_ntt::<F>(other)
+        }
+    }
+}
+
+impl<F: TwoAdicField> Mul for Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn mul(self, other: Polynome<F>) -> Polynome<F> {
+        &self * &other
+    }
+}
+
+impl<F: FriField> Mul<F> for &Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn mul(self, scalar: F) -> Polynome<F> {
+        self.scale(&scalar)
+    }
+}
+
+impl<F: FriField> Mul<F> for Polynome<F> {
+    type Output = Polynome<F>;
+
+    fn mul(self, scalar: F) -> Polynome<F> {
+        self.scale(&scalar)
     }
 }
 
@@ -103,6 +414,10 @@ impl Polynome<FieldElement> {
 mod tests {
 
     use super::*;
+    use crate::field_extension::QuadraticExtension;
+    use crate::field_provider_goldilocks::Goldilocks;
+    use crate::field_provider_v1::FieldElement;
+    use ff::Field;
 
     #[test]
     fn test_evaluate() {
@@ -198,6 +513,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_evaluate_over_domain() {
+        let coefficients = (0..12)
+            .map(|i| FieldElement::from(i * 5 + 1))
+            .collect::<Vec<FieldElement>>();
+        let p = Polynome::new_poly(&coefficients);
+        let domain = Domain::new_coset(16, FieldElement::from(7u64));
+
+        assert_eq!(
+            p.evaluate_over_domain(&domain),
+            p.evaluate_sliding(domain.elements())
+        );
+
+        // More coefficients than domain elements
+        let small_domain = Domain::new_coset(4, FieldElement::from(7u64));
+        assert_eq!(
+            p.evaluate_over_domain(&small_domain),
+            p.evaluate_sliding(small_domain.elements())
+        );
+    }
+
+    #[test]
+    fn test_interpolate_from_domain() {
+        let coefficients = vec![
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+        ];
+        let p = Polynome::new_poly(&coefficients);
+        let domain = Domain::new_coset(8, FieldElement::from(7u64));
+
+        let evaluations = p.evaluate_over_domain(&domain);
+        let result = Polynome::interpolate_from_domain(&evaluations, &domain);
+
+        assert_eq!(result.coefficients, coefficients);
+    }
+
     #[test]
     fn test_fold_with_beta() {
         let coefficients = vec![
@@ -211,7 +563,7 @@ mod tests {
         let p = Polynome::new_poly(&coefficients);
         let beta = FieldElement::from(2u64);
         let result = p.fold_with_beta(&beta);
-        assert_eq!(result.degree(), p.degree() / 2); //Ugly but works
+        assert_eq!(result.degree(), p.degree().map(|d| d / 2)); //Ugly but works
         assert_eq!(
             result.coefficients,
             vec![
@@ -221,4 +573,267 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_fold_in_extension() {
+        type E = QuadraticExtension<Goldilocks>;
+        let p = Polynome::new_poly(&(1..=9).map(Goldilocks::from).collect::<Vec<Goldilocks>>());
+        let beta = E::x() + E::from(3u64);
+        let domain = Domain::new_coset(8, Goldilocks::from(7u64));
+
+        let folded = p.fold_with_beta(&beta);
+        assert_eq!(folded.degree(), p.degree().map(|d| d / 2));
+
+        // f_next(x^2) = (f(x) + f(-x)) / 2 + beta * (f(x) - f(-x)) / (2x)
+        let x = domain[1];
+        let (fx, fmx) = (p.evaluate(&x), p.evaluate(&-x));
+        let two_inv = Goldilocks::from(2u64).invert().unwrap();
+        let expected = E::from_base((fx + fmx) * two_inv)
+            + beta * ((fx - fmx) * (x.double()).invert().unwrap());
+        assert_eq!(folded.evaluate(&E::from_base(x.square())), expected);
+
+        // Evaluations of the extension polynomial over the base field domain
+        let evaluations = folded.evaluate_over_domain(&domain);
+        (0..8).for_each(|i| assert_eq!(evaluations[i], folded.evaluate(&E::from_base(domain[i]))));
+        assert_eq!(
+            Polynome::interpolate_from_domain(&evaluations, &domain).coefficients,
+            folded.coefficients
+        );
+    }
+
+    #[test]
+    fn test_fold_with_factor() {
+        let coefficients = (1..=11)
+            .map(FieldElement::from)
+            .collect::<Vec<FieldElement>>();
+        let p = Polynome::new_poly(&coefficients);
+        let beta = FieldElement::from(3u64);
+
+        // Folding by 4 is folding twice by 2 with beta and beta^2
+        let folded = p.fold_with_factor(&beta, 4);
+        assert_eq!(folded.degree(), p.degree().map(|d| d / 4));
+        assert_eq!(
+            folded.coefficients,
+            p.fold_with_beta(&beta)
+                .fold_with_beta(&beta.square())
+                .coefficients
+        );
+
+        // 1 + 2b + 3b^2 + 4b^3 with b = 3
+        assert_eq!(folded.coefficients[0], FieldElement::from(142u64));
+        assert_eq!(
+            p.fold_with_factor(&beta, 16).coefficients,
+            vec![p.evaluate(&beta)]
+        );
+    }
+
+    #[test]
+    fn test_add_sub_neg() {
+        let p = Polynome::new_poly(&[
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+        ]);
+        let q = Polynome::new_poly(&[FieldElement::from(4u64), FieldElement::from(5u64)]);
+
+        assert_eq!(
+            (&p + &q).coefficients,
+            vec![
+                FieldElement::from(5u64),
+                FieldElement::from(7u64),
+                FieldElement::from(3u64)
+            ]
+        );
+        assert_eq!(
+            (&p - &q).coefficients,
+            vec![
+                -FieldElement::from(3u64),
+                -FieldElement::from(3u64),
+                FieldElement::from(3u64)
+            ]
+        );
+        assert_eq!(
+            (-&q).evaluate(&FieldElement::from(2u64)),
+            -FieldElement::from(14u64)
+        );
+
+        // Leading terms cancelling are removed
+        let r = Polynome::new_poly(&[FieldElement::from(9u64), -FieldElement::from(3u64)]);
+        let cancelled = &p.clone() + &(-&p + r.clone());
+        assert_eq!(cancelled.coefficients, r.coefficients);
+        assert!((p.clone() - p).is_zero());
+    }
+
+    #[test]
+    fn test_zero_degree() {
+        let p = Polynome::new_poly(&[FieldElement::from(1u64), FieldElement::from(2u64)]);
+        assert_eq!(p.degree(), Some(1));
+        assert_eq!((&p - &p).degree(), None);
+        assert_eq!(Polynome::new_poly(&[FieldElement::ZERO]).degree(), None);
+    }
+
+    #[test]
+    fn test_mul() {
+        let p = Polynome::new_poly(&[
+            FieldElement::from(1u64),
+            FieldElement::from(2u64),
+            FieldElement::from(3u64),
+        ]);
+        let q = Polynome::new_poly(&[FieldElement::from(4u64), FieldElement::from(5u64)]);
+
+        // (1 + 2x + 3x^2)(4 + 5x) = 4 + 13x + 22x^2 + 15x^3
+        assert_eq!(
+            (&p * &q).coefficients,
+            vec![
+                FieldElement::from(4u64),
+                FieldElement::from(13u64),
+                FieldElement::from(22u64),
+                FieldElement::from(15u64)
+            ]
+        );
+        assert_eq!(
+            (p.clone() * FieldElement::from(2u64)).coefficients,
+            vec![
+                FieldElement::from(2u64),
+                FieldElement::from(4u64),
+                FieldElement::from(6u64)
+            ]
+        );
+        assert!((p * FieldElement::ZERO).is_zero());
+    }
+
+    #[test]
+    fn test_mul_ntt() {
+        let p = Polynome::new_poly(
+            &(1..=70)
+                .map(|i| Goldilocks::from(i * i + 1))
+                .collect::<Vec<Goldilocks>>(),
+        );
+        let q = Polynome::new_poly(
+            &(1..=45)
+                .map(|i| Goldilocks::from(3 * i))
+                .collect::<Vec<Goldilocks>>(),
+        );
+
+        let product = &p * &q;
+        assert_eq!(
+            product.degree().unwrap(),
+            p.degree().unwrap() + q.degree().unwrap()
+        );
+        assert_eq!(product.coefficients, p.mul_schoolbook(&q).coefficients);
+
+        // Coefficients in the extension, NTT over the base field
+        type E = QuadraticExtension<Goldilocks>;
+        let pe = p.fold_with_factor(&(E::x() + E::from(2u64)), 2);
+        let qe = q.to_extension::<E>();
+        assert_eq!(
+            pe.mul_ntt::<Goldilocks>(&qe).coefficients,
+            pe.mul_schoolbook(&qe).coefficients
+        );
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let p = Polynome::new_poly(
+            &(1..=20)
+                .map(|i| FieldElement::from(i * 7))
+                .collect::<Vec<FieldElement>>(),
+        );
+        let d = Polynome::new_poly(&[
+            FieldElement::from(3u64),
+            FieldElement::from(0u64),
+            FieldElement::from(1u64),
+            FieldElement::from(5u64),
+        ]);
+
+        let (q, r) = p.div_rem(&d);
+        assert_eq!(
+            q.degree().unwrap(),
+            p.degree().unwrap() - d.degree().unwrap()
+        );
+        assert!(r.coefficients.len() <= d.degree().unwrap());
+        assert_eq!((&(&q * &d) + &r).coefficients, p.coefficients);
+
+        // Exact division
+        let (q, r) = (&p * &d).div_rem(&d);
+        assert_eq!(q.coefficients, p.coefficients);
+        assert!(r.is_zero());
+
+        // The divisor has a larger degree
+        let (q, r) = d.div_rem(&p);
+        assert!(q.is_zero());
+        assert_eq!(r.coefficients, d.coefficients);
+    }
+
+    #[test]
+    fn test_vanishing_polynomials() {
+        let points = (1..=5)
+            .map(|i| FieldElement::from(i * i + 2))
+            .collect::<Vec<FieldElement>>();
+        let vanishing = Polynome::vanishing_polynomial(&points);
+
+        assert_eq!(vanishing.degree(), Some(5));
+        assert_eq!(vanishing.coefficients[5], FieldElement::ONE);
+        points
+            .iter()
+            .for_each(|p| assert_eq!(vanishing.evaluate(p), FieldElement::ZERO));
+        assert_ne!(vanishing.evaluate(&FieldElement::ZERO), FieldElement::ZERO);
+
+        // x^n - shift^n vanishes on the coset of size n
+        let domain = Domain::new_coset(8, FieldElement::from(7u64));
+        let subgroup_vanishing =
+            Polynome::subgroup_vanishing_polynomial(8, &domain.shift().pow([8u64]));
+        assert_eq!(
+            subgroup_vanishing.coefficients,
+            Polynome::vanishing_polynomial(domain.elements()).coefficients
+        );
+    }
+
+    #[test]
+    fn test_lagrange_basis() {
+        let points = (0..4)
+            .map(|i| FieldElement::from(3 * i + 1))
+            .collect::<Vec<FieldElement>>();
+
+        for i in 0..4 {
+            let basis = Polynome::lagrange_basis(&points, i);
+            assert_eq!(basis.degree(), Some(3));
+            for (j, p) in points.iter().enumerate() {
+                let expected = if i == j {
+                    FieldElement::ONE
+                } else {
+                    FieldElement::ZERO
+                };
+                assert_eq!(basis.evaluate(p), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let p = Polynome::new_poly(
+            &(1..=6)
+                .map(|i| Goldilocks::from(i * 5 + 2))
+                .collect::<Vec<Goldilocks>>(),
+        );
+        let points = (0..6)
+            .map(|i| Goldilocks::from(i * i + 3))
+            .collect::<Vec<Goldilocks>>();
+
+        assert_eq!(
+            Polynome::interpolate(&points, &p.evaluate_sliding(&points)).coefficients,
+            p.coefficients
+        );
+
+        // Known roots
+        let values = vec![Goldilocks::ZERO; 5];
+        assert!(Polynome::interpolate(&points[..5], &values).is_zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "Interpolation points should be distinct")]
+    fn test_interpolate_same_points() {
+        let points = vec![FieldElement::from(2u64), FieldElement::from(2u64)];
+        Polynome::interpolate(&points, &points);
+    }
 }
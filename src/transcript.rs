@@ -0,0 +1,247 @@
+// Subject: Fiat-Shamir transcript
+//
+// Duplex sponge over the `TranscriptSponge` of a `MerkleHasher`, SHA-256 by default :
+// every absorbed message and every squeezed challenge updates the state, so a challenge
+// depends on everything sent before it.
+// The byte hashes run a hash chain over the encodings of the messages. Poseidon2 runs a
+// sponge over the BLS12-381 scalar field instead, which absorbs the Merkle digests and
+// the field elements as field elements, without hashing their bytes.
+// Labels separate the messages (and the protocols) from each other.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::field::FriField;
+use crate::hasher::{MerkleHasher, Sha256};
+
+pub(crate) const ABSORB_TAG: u8 = 0;
+pub(crate) const SQUEEZE_TAG: u8 = 1;
+
+pub trait TranscriptSponge: Clone + Debug + Default + PartialEq + Eq {
+    fn absorb(&mut self, label: &[u8], data: &[u8]);
+
+    // Merkle digests, as their bytes by default
+    fn absorb_digests(&mut self, label: &[u8], digests: &[[u8; 32]]) {
+        self.absorb(label, &digests.concat());
+    }
+
+    // Field elements, as their canonical encodings by default
+    fn absorb_field_elements<F: FriField>(&mut self, label: &[u8], elements: &[F]) {
+        let bytes = elements
+            .iter()
+            .flat_map(|element| element.to_bytes())
+            .collect::<Vec<u8>>();
+        self.absorb(label, &bytes);
+    }
+
+    // 32 bytes close to uniform, depending on everything absorbed before
+    fn squeeze(&mut self, label: &[u8]) -> [u8; 32];
+}
+
+// Hash chain of a byte hash : the state is the hash of the previous state and the message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashChain<H: MerkleHasher> {
+    state: [u8; 32],
+    hasher: PhantomData<H>,
+}
+
+impl<H: MerkleHasher> Default for HashChain<H> {
+    fn default() -> Self {
+        Self {
+            state: [0u8; 32],
+            hasher: PhantomData,
+        }
+    }
+}
+
+impl<H: MerkleHasher> HashChain<H> {
+    fn update(&mut self, tag: u8, label: &[u8], data: &[u8]) {
+        let mut input = Vec::with_capacity(1 + 32 + 16 + label.len() + data.len());
+        input.push(tag);
+        input.extend_from_slice(&self.state);
+        input.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        input.extend_from_slice(label);
+        input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        input.extend_from_slice(data);
+        self.state = H::hash(&input);
+    }
+}
+
+impl<H: MerkleHasher> TranscriptSponge for HashChain<H> {
+    fn absorb(&mut self, label: &[u8], data: &[u8]) {
+        self.update(ABSORB_TAG, label, data);
+    }
+
+    fn squeeze(&mut self, label: &[u8]) -> [u8; 32] {
+        self.update(SQUEEZE_TAG, label, &[]);
+        self.state
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transcript<H: MerkleHasher = Sha256> {
+    sponge: H::Sponge,
+}
+
+impl<H: MerkleHasher> Transcript<H> {
+    // The label separates the protocol using the transcript from any other one
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Self {
+            sponge: H::Sponge::default(),
+        };
+        transcript.absorb(b"protocol", label);
+        transcript
+    }
+
+    pub fn absorb(&mut self, label: &[u8], data: &[u8]) {
+        self.sponge.absorb(label, data);
+    }
+
+    pub fn absorb_digests(&mut self, label: &[u8], digests: &[[u8; 32]]) {
+        self.sponge.absorb_digests(label, digests);
+    }
+
+    pub fn absorb_field_elements<F: FriField>(&mut self, label: &[u8], elements: &[F]) {
+        self.sponge.absorb_field_elements(label, elements);
+    }
+
+    pub fn squeeze_bytes(&mut self, label: &[u8]) -> [u8; 32] {
+        self.sponge.squeeze(label)
+    }
+
+    // As many squeezes as needed by the field to sample an element
+    pub fn squeeze_field_element<F: FriField>(&mut self, label: &[u8]) -> F {
+        let len = F::uniform_bytes_len();
+        let mut bytes = Vec::with_capacity(len + 32);
+        while bytes.len() < len {
+            bytes.extend_from_slice(&self.squeeze_bytes(label));
+        }
+        F::from_uniform_bytes(&bytes[..len])
+    }
+
+    pub fn squeeze_index(&mut self, label: &[u8]) -> usize {
+        let bytes = self.squeeze_bytes(label);
+        u64::from_le_bytes(bytes[..8].try_into().expect("Slice of 8 bytes")) as usize
+    }
+
+    // Proof of work : the nonce is valid if the state reached by absorbing it
+    // starts with `bits` zero bits, the transcript itself is left unchanged
+    pub fn check_proof_of_work(&self, label: &[u8], bits: u32, nonce: u64) -> bool {
+        let mut transcript = self.clone();
+        transcript.absorb(label, &nonce.to_le_bytes());
+        leading_zero_bits(&transcript.squeeze_bytes(label)) >= bits
+    }
+
+    // Smallest valid nonce, about 2^bits attempts
+    pub fn grind(&self, label: &[u8], bits: u32) -> u64 {
+        (0..u64::MAX)
+            .find(|nonce| self.check_proof_of_work(label, bits, *nonce))
+            .expect("A proof of work nonce exists")
+    }
+}
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in bytes {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::field_provider_v1::FieldElement;
+
+    #[test]
+    fn test_transcript_determinism() {
+        let mut prover = Transcript::<Sha256>::new(b"test");
+        let mut verifier = Transcript::<Sha256>::new(b"test");
+
+        prover.absorb(b"root", b"0x1234");
+        verifier.absorb(b"root", b"0x1234");
+
+        assert_eq!(
+            prover.squeeze_field_element::<FieldElement>(b"beta"),
+            verifier.squeeze_field_element::<FieldElement>(b"beta")
+        );
+        assert_eq!(
+            prover.squeeze_index(b"query"),
+            verifier.squeeze_index(b"query")
+        );
+    }
+
+    #[test]
+    fn test_transcript_domain_separation() {
+        let mut t1 = Transcript::<Sha256>::new(b"protocol A");
+        let mut t2 = Transcript::<Sha256>::new(b"protocol B");
+        assert_ne!(t1.squeeze_bytes(b"beta"), t2.squeeze_bytes(b"beta"));
+
+        let mut t1 = Transcript::<Sha256>::new(b"test");
+        let mut t2 = Transcript::<Sha256>::new(b"test");
+        assert_ne!(t1.squeeze_bytes(b"beta"), t2.squeeze_bytes(b"query"));
+
+        // Label and data boundaries are not ambiguous
+        let mut t1 = Transcript::<Sha256>::new(b"test");
+        let mut t2 = Transcript::<Sha256>::new(b"test");
+        t1.absorb(b"ab", b"c");
+        t2.absorb(b"a", b"bc");
+        assert_ne!(t1, t2);
+    }
+
+    #[test]
+    fn test_transcript_depends_on_absorbed_data() {
+        let mut t1 = Transcript::<Sha256>::new(b"test");
+        let mut t2 = Transcript::<Sha256>::new(b"test");
+        t1.absorb(b"root", b"0x1234");
+        t2.absorb(b"root", b"0x1235");
+
+        assert_ne!(
+            t1.squeeze_field_element::<FieldElement>(b"beta"),
+            t2.squeeze_field_element::<FieldElement>(b"beta")
+        );
+    }
+
+    #[test]
+    fn test_successive_challenges_differ() {
+        let mut t = Transcript::<Sha256>::new(b"test");
+        let beta_1 = t.squeeze_field_element::<FieldElement>(b"beta");
+        let beta_2 = t.squeeze_field_element::<FieldElement>(b"beta");
+
+        assert_ne!(beta_1, beta_2);
+    }
+
+    #[test]
+    fn test_hash_chain_absorbs_encodings() {
+        let elements = [FieldElement::from(3u64), FieldElement::from(5u64)];
+        let mut t1 = Transcript::<Sha256>::new(b"test");
+        let mut t2 = Transcript::<Sha256>::new(b"test");
+        t1.absorb_field_elements(b"values", &elements);
+        t2.absorb(
+            b"values",
+            &[elements[0].to_bytes(), elements[1].to_bytes()].concat(),
+        );
+        assert_eq!(t1, t2);
+
+        t1.absorb_digests(b"root", &[[1u8; 32], [2u8; 32]]);
+        t2.absorb(b"root", &[[1u8; 32], [2u8; 32]].concat());
+        assert_eq!(t1, t2);
+    }
+
+    #[test]
+    fn test_proof_of_work() {
+        let mut transcript = Transcript::<Sha256>::new(b"test");
+        transcript.absorb(b"root", b"0x1234");
+
+        let nonce = transcript.grind(b"pow", 8);
+        assert!(transcript.check_proof_of_work(b"pow", 8, nonce));
+        assert!((0..nonce).all(|n| !transcript.check_proof_of_work(b"pow", 8, n)));
+
+        assert_eq!(leading_zero_bits(&[0, 0x10, 0xff]), 11);
+        assert_eq!(leading_zero_bits(&[0, 0]), 16);
+    }
+}